@@ -0,0 +1,152 @@
+//! Shared helpers for the `bincode`, `borsh` and `postcard` impls.
+//!
+//! Decoding reads the length prefix first and copies the contents straight
+//! into a stack buffer when they fit inline, only strings which would end up
+//! on the heap anyway are read into a `Vec`.
+
+#[cfg(any(feature = "bincode", feature = "borsh"))]
+use alloc::{string::String, vec::Vec};
+#[cfg(any(feature = "bincode", feature = "borsh"))]
+use core::str::{self, Utf8Error};
+
+#[cfg(feature = "bincode")]
+use bincode::{
+    de::{Decode, Decoder, read::Reader},
+    error::DecodeError,
+};
+
+#[cfg(feature = "borsh")]
+use alloc::string::ToString;
+#[cfg(feature = "borsh")]
+use borsh::{
+    BorshDeserialize,
+    io::{Error, ErrorKind, Read},
+};
+
+/// Long strings are read in chunks of this size so that a bogus length prefix
+/// can't trigger an arbitrarily large allocation up front.
+#[cfg(any(feature = "bincode", feature = "borsh"))]
+const CHUNK_SIZE: usize = 4096;
+
+/// Read a string of `len` bytes using `read`, via a stack buffer of `N` bytes
+/// if it fits.
+#[cfg(any(feature = "bincode", feature = "borsh"))]
+pub(crate) fn read_str<const N: usize, T, E>(
+    len: usize,
+    mut read: impl FnMut(&mut [u8]) -> Result<(), E>,
+    invalid: impl FnOnce(Utf8Error) -> E,
+) -> Result<T, E>
+where
+    T: for<'a> From<&'a str> + From<String>,
+{
+    if len <= N {
+        let mut buf = [0u8; N];
+        read(&mut buf[..len])?;
+        return str::from_utf8(&buf[..len]).map(T::from).map_err(invalid);
+    }
+    let mut buf = Vec::new();
+    while buf.len() < len {
+        let start = buf.len();
+        buf.resize(start + (len - start).min(CHUNK_SIZE), 0);
+        read(&mut buf[start..])?;
+    }
+    String::from_utf8(buf)
+        .map(T::from)
+        .map_err(|e| invalid(e.utf8_error()))
+}
+
+#[cfg(feature = "bincode")]
+pub(crate) fn bincode_len<D: Decoder>(decoder: &mut D) -> Result<usize, DecodeError> {
+    let len = <u64 as Decode<D::Context>>::decode(decoder)?;
+    let len = usize::try_from(len).map_err(|_| DecodeError::OutsideUsizeRange(len))?;
+    decoder.claim_container_read::<u8>(len)?;
+    Ok(len)
+}
+
+#[cfg(feature = "bincode")]
+pub(crate) fn bincode_decode<const N: usize, T, D>(decoder: &mut D) -> Result<T, DecodeError>
+where
+    T: for<'a> From<&'a str> + From<String>,
+    D: Decoder,
+{
+    let len = bincode_len(decoder)?;
+    read_str::<N, T, _>(
+        len,
+        |buf| decoder.reader().read(buf),
+        |inner| DecodeError::Utf8 { inner },
+    )
+}
+
+#[cfg(feature = "borsh")]
+pub(crate) fn borsh_len<R: Read>(reader: &mut R) -> Result<usize, Error> {
+    let len = u32::deserialize_reader(reader)?;
+    usize::try_from(len).map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))
+}
+
+#[cfg(feature = "borsh")]
+pub(crate) fn borsh_deserialize<const N: usize, T, R>(reader: &mut R) -> Result<T, Error>
+where
+    T: for<'a> From<&'a str> + From<String>,
+    R: Read,
+{
+    let len = borsh_len(reader)?;
+    read_str::<N, T, _>(
+        len,
+        |buf| reader.read_exact(buf),
+        |e| Error::new(ErrorKind::InvalidData, e.to_string()),
+    )
+}
+
+/// Number of bytes needed to encode `n` as a postcard varint.
+#[cfg(feature = "postcard")]
+pub(crate) const fn varint_size(mut n: usize) -> usize {
+    let mut size = 1;
+    while n >= 0x80 {
+        n >>= 7;
+        size += 1;
+    }
+    size
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(any(feature = "bincode", feature = "borsh"))]
+    fn read_from(mut input: &[u8]) -> impl FnMut(&mut [u8]) -> Result<(), ()> + '_ {
+        move |buf: &mut [u8]| {
+            let (head, tail) = input.split_at(buf.len());
+            buf.copy_from_slice(head);
+            input = tail;
+            Ok(())
+        }
+    }
+
+    #[cfg(any(feature = "bincode", feature = "borsh"))]
+    #[test]
+    fn test_read_str() {
+        use crate::{SmallString, StackString, binary::read_str};
+
+        let s: SmallString<8> = read_str::<8, _, _>(5, read_from(b"hello"), |_| ()).unwrap();
+        assert!(s.is_inline());
+        assert_eq!(s, "hello");
+
+        let long = "a string which is longer than the chunk size ".repeat(100);
+        let s: StackString =
+            read_str::<24, _, _>(long.len(), read_from(long.as_bytes()), |_| ()).unwrap();
+        assert_eq!(s, long);
+
+        let result = read_str::<8, SmallString<8>, _>(3, read_from(b"ab\xff"), |_| ());
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn test_varint_size() {
+        use crate::binary::varint_size;
+
+        assert_eq!(varint_size(0), 1);
+        assert_eq!(varint_size(127), 1);
+        assert_eq!(varint_size(128), 2);
+        assert_eq!(varint_size(253), 2);
+        assert_eq!(varint_size(16384), 3);
+    }
+}
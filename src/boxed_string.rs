@@ -0,0 +1,139 @@
+//! Heap representation of `SmallString`.
+//!
+//! A `String` is three words with an alignment of 8 on 64 bit targets, so an
+//! enum holding one next to a 24 byte inline buffer needs a separate tag.
+//! `BoxedString` stores the same parts packed into 23 bytes, which leaves
+//! the last byte of a 24 byte `SmallString` for the length of the inline
+//! buffer and the tag.
+
+use alloc::string::String;
+use core::{mem::ManuallyDrop, ptr::NonNull, slice, str};
+
+/// Number of bytes used to store the capacity.  No allocation can reach
+/// 2^56 bytes on a 64 bit target, so the top byte is always zero and is left
+/// out.
+const CAPACITY_BYTES: usize = if usize::BITS == 64 {
+    7
+} else {
+    size_of::<usize>()
+};
+
+/// The parts of a `String`, packed so that it has an alignment of 1.
+#[repr(C, packed)]
+pub(crate) struct BoxedString {
+    ptr: NonNull<u8>,
+    len: usize,
+    capacity: [u8; CAPACITY_BYTES],
+}
+
+// SAFETY: `BoxedString` owns its buffer in the same way as `String`.
+unsafe impl Send for BoxedString {}
+// SAFETY: `BoxedString` only hands out shared access through `&self`.
+unsafe impl Sync for BoxedString {}
+
+impl BoxedString {
+    /// # Panics
+    /// Panics if the capacity of `s` doesn't fit in 56 bits, which can't
+    /// happen for a real allocation.
+    pub(crate) fn new(s: String) -> Self {
+        // the pointer is taken from the `Vec` rather than through `str`, so
+        // that it is valid for the whole allocation
+        let mut v = ManuallyDrop::new(s.into_bytes());
+        let capacity = v.capacity().to_le_bytes();
+        assert!(
+            capacity[CAPACITY_BYTES..].iter().all(|b| *b == 0),
+            "capacity overflow"
+        );
+        Self {
+            // SAFETY: the buffer of a `Vec` is never null, even when it
+            // hasn't allocated.
+            ptr: unsafe { NonNull::new_unchecked(v.as_mut_ptr()) },
+            len: v.len(),
+            capacity: capacity[..CAPACITY_BYTES].try_into().unwrap(),
+        }
+    }
+
+    fn parts(&self) -> (*mut u8, usize, usize) {
+        let mut capacity = [0; size_of::<usize>()];
+        capacity[..CAPACITY_BYTES].copy_from_slice(&self.capacity);
+        (self.ptr.as_ptr(), self.len, usize::from_le_bytes(capacity))
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.parts().2
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        let (ptr, len, _) = self.parts();
+        // SAFETY: the parts came from a `String`, which is borrowed along with
+        // `self`.
+        unsafe { str::from_utf8_unchecked(slice::from_raw_parts(ptr, len)) }
+    }
+
+    pub(crate) fn as_mut_str(&mut self) -> &mut str {
+        let (ptr, len, _) = self.parts();
+        // SAFETY: the parts came from a `String`, which is mutably borrowed
+        // along with `self`.
+        unsafe { str::from_utf8_unchecked_mut(slice::from_raw_parts_mut(ptr, len)) }
+    }
+
+    pub(crate) fn into_string(self) -> String {
+        let (ptr, len, capacity) = ManuallyDrop::new(self).parts();
+        // SAFETY: the parts came from a `String` and ownership is moved back.
+        unsafe { String::from_raw_parts(ptr, len, capacity) }
+    }
+}
+
+impl Drop for BoxedString {
+    fn drop(&mut self) {
+        let (ptr, len, capacity) = self.parts();
+        // SAFETY: the parts came from a `String` which is dropped only here.
+        drop(unsafe { String::from_raw_parts(ptr, len, capacity) });
+    }
+}
+
+impl Clone for BoxedString {
+    fn clone(&self) -> Self {
+        Self::new(self.as_str().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use crate::boxed_string::BoxedString;
+
+    #[test]
+    fn test_round_trip() {
+        let mut s = String::with_capacity(100);
+        s.push_str("hello");
+        let ptr = s.as_ptr();
+
+        let mut b = BoxedString::new(s);
+        assert_eq!(b.as_str(), "hello");
+        assert_eq!(b.capacity(), 100);
+        b.as_mut_str().make_ascii_uppercase();
+
+        let c = b.clone();
+        assert_eq!(c.as_str(), "HELLO");
+        assert_ne!(c.as_str().as_ptr(), ptr);
+
+        let s = b.into_string();
+        assert_eq!(s, "HELLO");
+        assert_eq!(s.as_ptr(), ptr);
+        assert_eq!(s.capacity(), 100);
+
+        let b = BoxedString::new(String::new());
+        assert_eq!(b.as_str(), "");
+        assert_eq!(b.capacity(), 0);
+    }
+
+    #[test]
+    fn test_sizeof() {
+        if size_of::<usize>() == 8 {
+            assert_eq!(size_of::<BoxedString>(), 23);
+            assert_eq!(align_of::<BoxedString>(), 1);
+        }
+    }
+}
@@ -0,0 +1,881 @@
+use alloc::{borrow::Cow, string::String};
+use arrayvec::ArrayString;
+use core::marker::PhantomData;
+use core::{
+    borrow::{Borrow, BorrowMut},
+    fmt,
+    fmt::Write as FmtWrite,
+    ops::{Deref, DerefMut},
+    str,
+    str::FromStr,
+};
+use derive_more::{Display, Error};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{Error as DeError, Unexpected, Visitor},
+};
+#[cfg(feature = "std")]
+use std::{ffi::OsStr, path::Path};
+
+#[cfg(feature = "postgres_types")]
+use bytes::BytesMut;
+#[cfg(feature = "postgres_types")]
+use postgres_types::{FromSql, IsNull, ToSql, Type};
+
+#[cfg(feature = "utoipa_types")]
+use utoipa::{PartialSchema, ToSchema};
+
+#[cfg(feature = "axum_types")]
+use axum::response::IntoResponse;
+
+#[cfg(feature = "axum_types")]
+use axum::body::Body;
+
+#[cfg(feature = "async_graphql")]
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
+
+use crate::{SmallCow, SmallString, StackCow, StackString};
+
+#[cfg(any(feature = "bincode", feature = "borsh", feature = "postcard"))]
+use crate::binary;
+
+#[cfg(any(feature = "bincode", feature = "borsh"))]
+use alloc::string::ToString;
+
+#[cfg(feature = "bincode")]
+use bincode::{
+    de::{BorrowDecoder, Decoder},
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+};
+
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[cfg(any(feature = "arbitrary", feature = "proptest", feature = "quickcheck"))]
+use crate::generators;
+
+#[cfg(feature = "proptest")]
+use proptest::strategy::{BoxedStrategy, Strategy};
+
+#[cfg(feature = "smart_string")]
+use crate::SmartString;
+
+/// Error returned when a value does not fit in a `FixedString`
+#[derive(Display, Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[display("string of length {len} exceeds capacity {capacity}")]
+pub struct CapacityError {
+    /// Length in bytes of the rejected value
+    pub len: usize,
+    /// Capacity in bytes of the `FixedString`
+    pub capacity: usize,
+}
+
+/// A string stored inline in a buffer of `CAP` bytes which never spills to
+/// the heap, any operation that would exceed `CAP` returns a `CapacityError`
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct FixedString<const CAP: usize>(ArrayString<CAP>);
+
+impl<const CAP: usize> FixedString<CAP> {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self(ArrayString::new())
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        CAP
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn remaining_capacity(&self) -> usize {
+        self.0.remaining_capacity()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    #[inline]
+    pub fn as_mut_str(&mut self) -> &mut str {
+        self.0.as_mut_str()
+    }
+
+    /// Construct a `FixedString` from a `&str`
+    /// # Errors
+    ///
+    /// Will return an Error if the string is longer than `CAP`
+    pub fn try_from_str(s: &str) -> Result<Self, CapacityError> {
+        ArrayString::from(s)
+            .map(Self)
+            .map_err(|_| Self::capacity_error(s.len()))
+    }
+
+    /// Append a `&str`, leaving the string unchanged if it doesn't fit
+    /// # Errors
+    ///
+    /// Will return an Error if the resulting string would be longer than `CAP`
+    pub fn push_str(&mut self, s: &str) -> Result<(), CapacityError> {
+        self.0
+            .try_push_str(s)
+            .map_err(|_| Self::capacity_error(self.len() + s.len()))
+    }
+
+    /// Append a `char`, leaving the string unchanged if it doesn't fit
+    /// # Errors
+    ///
+    /// Will return an Error if the resulting string would be longer than `CAP`
+    pub fn push(&mut self, c: char) -> Result<(), CapacityError> {
+        self.0
+            .try_push(c)
+            .map_err(|_| Self::capacity_error(self.len() + c.len_utf8()))
+    }
+
+    /// Write a formatted value into a new `FixedString`
+    /// # Errors
+    ///
+    /// Will return an Error if the formatted value is longer than `CAP`
+    pub fn from_display(buf: impl fmt::Display) -> Result<Self, CapacityError> {
+        let mut s = Self::new();
+        if write!(s, "{buf}").is_ok() {
+            Ok(s)
+        } else {
+            let mut counter = LengthCounter(0);
+            write!(counter, "{buf}").unwrap_or(());
+            Err(Self::capacity_error(counter.0))
+        }
+    }
+
+    /// Split the string into two at the given index.
+    ///
+    /// Returns the content to the right of the index as a new string, and
+    /// removes it from the original.
+    ///
+    /// If the index doesn't fall on a UTF-8 character boundary, this method
+    /// panics.
+    #[must_use]
+    pub fn split_off(&mut self, index: usize) -> Self {
+        let st = self.as_str();
+        assert!(st.is_char_boundary(index));
+        let result = Self(ArrayString::from(&st[index..]).unwrap_or_default());
+        self.0.truncate(index);
+        result
+    }
+
+    pub fn truncate(&mut self, new_len: usize) {
+        self.0.truncate(new_len);
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> ArrayString<CAP> {
+        self.0
+    }
+
+    fn capacity_error(len: usize) -> CapacityError {
+        CapacityError { len, capacity: CAP }
+    }
+}
+
+struct LengthCounter(usize);
+
+impl fmt::Write for LengthCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+impl<const CAP: usize> TryFrom<&str> for FixedString<CAP> {
+    type Error = CapacityError;
+    fn try_from(item: &str) -> Result<Self, Self::Error> {
+        Self::try_from_str(item)
+    }
+}
+
+impl<const CAP: usize> TryFrom<String> for FixedString<CAP> {
+    type Error = CapacityError;
+    fn try_from(item: String) -> Result<Self, Self::Error> {
+        Self::try_from_str(&item)
+    }
+}
+
+impl<const CAP: usize> TryFrom<&String> for FixedString<CAP> {
+    type Error = CapacityError;
+    fn try_from(item: &String) -> Result<Self, Self::Error> {
+        Self::try_from_str(item)
+    }
+}
+
+impl<'a, const CAP: usize> TryFrom<Cow<'a, str>> for FixedString<CAP> {
+    type Error = CapacityError;
+    fn try_from(item: Cow<'a, str>) -> Result<Self, Self::Error> {
+        Self::try_from_str(&item)
+    }
+}
+
+impl<const CAP: usize> TryFrom<StackString> for FixedString<CAP> {
+    type Error = CapacityError;
+    fn try_from(item: StackString) -> Result<Self, Self::Error> {
+        Self::try_from_str(&item)
+    }
+}
+
+impl<const CAP: usize> TryFrom<&StackString> for FixedString<CAP> {
+    type Error = CapacityError;
+    fn try_from(item: &StackString) -> Result<Self, Self::Error> {
+        Self::try_from_str(item)
+    }
+}
+
+impl<const CAP: usize, const CAP1: usize> TryFrom<SmallString<CAP1>> for FixedString<CAP> {
+    type Error = CapacityError;
+    fn try_from(item: SmallString<CAP1>) -> Result<Self, Self::Error> {
+        Self::try_from_str(&item)
+    }
+}
+
+impl<const CAP: usize> TryFrom<StackCow<'_>> for FixedString<CAP> {
+    type Error = CapacityError;
+    fn try_from(item: StackCow<'_>) -> Result<Self, Self::Error> {
+        Self::try_from_str(&item)
+    }
+}
+
+impl<const CAP: usize, const CAP1: usize> TryFrom<SmallCow<'_, CAP1>> for FixedString<CAP> {
+    type Error = CapacityError;
+    fn try_from(item: SmallCow<'_, CAP1>) -> Result<Self, Self::Error> {
+        Self::try_from_str(&item)
+    }
+}
+
+#[cfg(feature = "smart_string")]
+impl<const CAP: usize> TryFrom<SmartString> for FixedString<CAP> {
+    type Error = CapacityError;
+    fn try_from(item: SmartString) -> Result<Self, Self::Error> {
+        Self::try_from_str(&item)
+    }
+}
+
+impl<const CAP: usize> From<ArrayString<CAP>> for FixedString<CAP> {
+    fn from(item: ArrayString<CAP>) -> Self {
+        Self(item)
+    }
+}
+
+impl<const CAP: usize> From<FixedString<CAP>> for ArrayString<CAP> {
+    fn from(item: FixedString<CAP>) -> Self {
+        item.0
+    }
+}
+
+impl<const CAP: usize> From<FixedString<CAP>> for String {
+    fn from(item: FixedString<CAP>) -> Self {
+        item.as_str().into()
+    }
+}
+
+impl<const CAP: usize> From<&FixedString<CAP>> for String {
+    fn from(item: &FixedString<CAP>) -> Self {
+        item.as_str().into()
+    }
+}
+
+impl<const CAP: usize> From<FixedString<CAP>> for StackString {
+    fn from(item: FixedString<CAP>) -> Self {
+        item.as_str().into()
+    }
+}
+
+impl<const CAP: usize> From<FixedString<CAP>> for SmallString<CAP> {
+    fn from(item: FixedString<CAP>) -> Self {
+        item.as_str().into()
+    }
+}
+
+impl<const CAP: usize> From<FixedString<CAP>> for StackCow<'_> {
+    fn from(item: FixedString<CAP>) -> Self {
+        StackCow::Owned(item.into())
+    }
+}
+
+impl<const CAP: usize> From<FixedString<CAP>> for SmallCow<'_, CAP> {
+    fn from(item: FixedString<CAP>) -> Self {
+        SmallCow::Owned(item.into())
+    }
+}
+
+#[cfg(feature = "smart_string")]
+impl<const CAP: usize> From<FixedString<CAP>> for SmartString {
+    fn from(item: FixedString<CAP>) -> Self {
+        item.as_str().into()
+    }
+}
+
+impl<'a, const CAP: usize> From<&'a FixedString<CAP>> for &'a str {
+    fn from(item: &FixedString<CAP>) -> &str {
+        item.as_str()
+    }
+}
+
+impl<const CAP: usize> From<FixedString<CAP>> for Cow<'_, str> {
+    fn from(item: FixedString<CAP>) -> Self {
+        Cow::Owned(item.into())
+    }
+}
+
+impl<const CAP: usize> Serialize for FixedString<CAP> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de, const CAP: usize> Deserialize<'de> for FixedString<CAP> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(FixedStringVisitor(PhantomData))
+    }
+}
+
+struct FixedStringVisitor<const CAP: usize>(PhantomData<*const FixedString<CAP>>);
+
+impl<const CAP: usize> Visitor<'_> for FixedStringVisitor<CAP> {
+    type Value = FixedString<CAP>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a string of at most {CAP} bytes")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: DeError,
+    {
+        FixedString::try_from_str(v).map_err(|e| DeError::invalid_length(e.len, &self))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: DeError,
+    {
+        match str::from_utf8(v) {
+            Ok(s) => self.visit_str(s),
+            Err(_) => Err(DeError::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+}
+
+impl<const CAP: usize> Borrow<str> for FixedString<CAP> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const CAP: usize> BorrowMut<str> for FixedString<CAP> {
+    fn borrow_mut(&mut self) -> &mut str {
+        self.as_mut_str()
+    }
+}
+
+impl<const CAP: usize> fmt::Display for FixedString<CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const CAP: usize> fmt::Write for FixedString<CAP> {
+    fn write_str(&mut self, s: &str) -> Result<(), fmt::Error> {
+        self.push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const CAP: usize> AsRef<str> for FixedString<CAP> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const CAP: usize> AsRef<[u8]> for FixedString<CAP> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_str().as_ref()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const CAP: usize> AsRef<OsStr> for FixedString<CAP> {
+    fn as_ref(&self) -> &OsStr {
+        self.as_str().as_ref()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const CAP: usize> AsRef<Path> for FixedString<CAP> {
+    fn as_ref(&self) -> &Path {
+        Path::new(self)
+    }
+}
+
+impl<const CAP: usize> FromStr for FixedString<CAP> {
+    type Err = CapacityError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from_str(s)
+    }
+}
+
+impl<const CAP: usize> Deref for FixedString<CAP> {
+    type Target = str;
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl<const CAP: usize> DerefMut for FixedString<CAP> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_str()
+    }
+}
+
+impl<'a, const CAP: usize> PartialEq<Cow<'a, str>> for FixedString<CAP> {
+    #[inline]
+    fn eq(&self, other: &Cow<'a, str>) -> bool {
+        PartialEq::eq(&self[..], &other[..])
+    }
+}
+
+impl<'a, const CAP: usize> PartialOrd<Cow<'a, str>> for FixedString<CAP> {
+    fn partial_cmp(&self, other: &Cow<'a, str>) -> Option<core::cmp::Ordering> {
+        PartialOrd::partial_cmp(&self[..], &other[..])
+    }
+}
+
+impl<const CAP: usize> PartialEq<String> for FixedString<CAP> {
+    #[inline]
+    fn eq(&self, other: &String) -> bool {
+        PartialEq::eq(&self[..], &other[..])
+    }
+}
+
+impl<const CAP: usize> PartialOrd<String> for FixedString<CAP> {
+    fn partial_cmp(&self, other: &String) -> Option<core::cmp::Ordering> {
+        PartialOrd::partial_cmp(&self[..], &other[..])
+    }
+}
+
+impl<const CAP: usize> PartialEq<str> for FixedString<CAP> {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        PartialEq::eq(self.as_str(), other)
+    }
+}
+
+impl<const CAP: usize> PartialOrd<str> for FixedString<CAP> {
+    fn partial_cmp(&self, other: &str) -> Option<core::cmp::Ordering> {
+        PartialOrd::partial_cmp(&self[..], other)
+    }
+}
+
+impl<const CAP: usize> PartialEq<&str> for FixedString<CAP> {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        PartialEq::eq(&self.as_str(), other)
+    }
+}
+
+impl<const CAP: usize> PartialOrd<&str> for FixedString<CAP> {
+    fn partial_cmp(&self, other: &&str) -> Option<core::cmp::Ordering> {
+        PartialOrd::partial_cmp(&self[..], &other[..])
+    }
+}
+
+#[cfg(feature = "postgres_types")]
+impl<'a, const CAP: usize> FromSql<'a> for FixedString<CAP> {
+    fn from_sql(
+        ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let s = <&'a str as FromSql>::from_sql(ty, raw)?;
+        Ok(Self::try_from_str(s)?)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <&'a str as FromSql>::accepts(ty)
+    }
+}
+
+#[cfg(feature = "postgres_types")]
+impl<const CAP: usize> ToSql for FixedString<CAP> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn std::error::Error + Sync + Send>>
+    where
+        Self: Sized,
+    {
+        ToSql::to_sql(&self.as_str(), ty, out)
+    }
+
+    fn accepts(ty: &Type) -> bool
+    where
+        Self: Sized,
+    {
+        <String as ToSql>::accepts(ty)
+    }
+
+    fn to_sql_checked(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        self.as_str().to_sql_checked(ty, out)
+    }
+}
+
+#[cfg(feature = "utoipa_types")]
+impl<const CAP: usize> PartialSchema for FixedString<CAP> {
+    fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
+        str::schema()
+    }
+}
+
+#[cfg(feature = "utoipa_types")]
+impl<const CAP: usize> ToSchema for FixedString<CAP> {
+    fn name() -> Cow<'static, str> {
+        str::name()
+    }
+}
+
+#[cfg(feature = "axum_types")]
+impl<const CAP: usize> IntoResponse for FixedString<CAP> {
+    fn into_response(self) -> axum::response::Response {
+        let s: String = self.into();
+        s.into_response()
+    }
+}
+
+#[cfg(feature = "axum_types")]
+impl<const CAP: usize> From<FixedString<CAP>> for Body {
+    fn from(value: FixedString<CAP>) -> Self {
+        let s: String = value.into();
+        s.into()
+    }
+}
+
+/// Allow FixedString to be used as graphql scalar value
+#[cfg(feature = "async_graphql")]
+#[Scalar]
+impl<const CAP: usize> ScalarType for FixedString<CAP> {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        if let Value::String(s) = &value {
+            Self::try_from_str(s).map_err(InputValueError::custom)
+        } else {
+            Err(InputValueError::expected_type(value))
+        }
+    }
+
+    fn is_valid(value: &Value) -> bool {
+        matches!(value, Value::String(s) if s.len() <= CAP)
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, const CAP: usize> arbitrary::Arbitrary<'a> for FixedString<CAP> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        generators::arbitrary_string(u, &[CAP])
+            .map(|s| Self::try_from_str(&generators::truncate_to(s, CAP)).unwrap())
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<const CAP: usize> proptest::arbitrary::Arbitrary for FixedString<CAP> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        generators::string_strategy(&[CAP])
+            .prop_map(|s| Self::try_from_str(&generators::truncate_to(s, CAP)).unwrap())
+            .boxed()
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl<const CAP: usize> quickcheck::Arbitrary for FixedString<CAP> {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        let s = generators::quickcheck_string(g, &[CAP]);
+        Self::try_from_str(&generators::truncate_to(s, CAP)).unwrap()
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let s = String::from(self.as_str());
+        Box::new(quickcheck::Arbitrary::shrink(&s).filter_map(|s| Self::try_from_str(&s).ok()))
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<const CAP: usize> bincode::Encode for FixedString<CAP> {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        bincode::Encode::encode(self.as_str(), encoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<Context, const CAP: usize> bincode::Decode<Context> for FixedString<CAP> {
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        use bincode::de::read::Reader;
+
+        let len = binary::bincode_len(decoder)?;
+        if len > CAP {
+            return Err(DecodeError::OtherString(
+                Self::capacity_error(len).to_string(),
+            ));
+        }
+        let mut buf = [0u8; CAP];
+        decoder.reader().read(&mut buf[..len])?;
+        let s = str::from_utf8(&buf[..len]).map_err(|inner| DecodeError::Utf8 { inner })?;
+        Ok(Self(ArrayString::from(s).expect("length checked above")))
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<'de, Context, const CAP: usize> bincode::BorrowDecode<'de, Context> for FixedString<CAP> {
+    fn borrow_decode<D: BorrowDecoder<'de, Context = Context>>(
+        decoder: &mut D,
+    ) -> Result<Self, DecodeError> {
+        bincode::Decode::decode(decoder)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<const CAP: usize> BorshSerialize for FixedString<CAP> {
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        BorshSerialize::serialize(self.as_str(), writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<const CAP: usize> BorshDeserialize for FixedString<CAP> {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        use borsh::io::{Error, ErrorKind};
+
+        let len = binary::borsh_len(reader)?;
+        if len > CAP {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                Self::capacity_error(len).to_string(),
+            ));
+        }
+        let mut buf = [0u8; CAP];
+        reader.read_exact(&mut buf[..len])?;
+        let s = str::from_utf8(&buf[..len])
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+        Ok(Self(ArrayString::from(s).expect("length checked above")))
+    }
+}
+
+/// The length prefix plus at most `CAP` bytes.
+#[cfg(feature = "postcard")]
+impl<const CAP: usize> postcard::experimental::max_size::MaxSize for FixedString<CAP> {
+    const POSTCARD_MAX_SIZE: usize = CAP + binary::varint_size(CAP);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use crate::{
+        SmallString, StackString,
+        fixed_string::{CapacityError, FixedString},
+    };
+
+    #[test]
+    fn test_default() {
+        assert_eq!(FixedString::<1>::new(), FixedString::<1>::default());
+        assert_eq!(FixedString::<5>::new().capacity(), 5);
+    }
+
+    #[test]
+    fn test_try_from_str() {
+        let s = FixedString::<5>::try_from_str("12345").unwrap();
+        assert_eq!(s, "12345");
+        assert_eq!(s.remaining_capacity(), 0);
+
+        let e = FixedString::<5>::try_from_str("123456").unwrap_err();
+        assert_eq!(
+            e,
+            CapacityError {
+                len: 6,
+                capacity: 5
+            }
+        );
+        assert_eq!(e.to_string(), "string of length 6 exceeds capacity 5");
+
+        let s: Result<FixedString<5>, _> = "123456".parse();
+        assert!(s.is_err());
+        let s: Result<FixedString<5>, _> = String::from("1234").try_into();
+        assert_eq!(s.unwrap(), "1234");
+    }
+
+    #[test]
+    fn test_push_str() {
+        let mut s = FixedString::<5>::new();
+        s.push_str("123").unwrap();
+        s.push('4').unwrap();
+        let e = s.push_str("56").unwrap_err();
+        assert_eq!(e.len, 6);
+        assert_eq!(s, "1234");
+        s.push('5').unwrap();
+        assert!(s.push('6').is_err());
+        assert_eq!(s, "12345");
+    }
+
+    #[test]
+    fn test_write_fixedstring() {
+        let mut s = FixedString::<5>::new();
+        write!(&mut s, "12345").unwrap();
+        assert_eq!(s.as_str(), "12345");
+
+        let mut s = FixedString::<5>::new();
+        assert!(write!(&mut s, "123456789").is_err());
+    }
+
+    #[test]
+    fn test_from_display() {
+        let s = FixedString::<5>::from_display(1234).unwrap();
+        assert_eq!(s, "1234");
+        let e = FixedString::<5>::from_display(1_234_567).unwrap_err();
+        assert_eq!(
+            e,
+            CapacityError {
+                len: 7,
+                capacity: 5
+            }
+        );
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut s0 = "hello there".to_string();
+        let s1 = s0.split_off(3);
+        let mut s2 = FixedString::<20>::try_from_str("hello there").unwrap();
+        let s3 = s2.split_off(3);
+        assert_eq!(s0.as_str(), s2.as_str());
+        assert_eq!(s1.as_str(), s3.as_str());
+    }
+
+    #[test]
+    fn test_conversions() {
+        let s = FixedString::<20>::try_from_str("Hello").unwrap();
+        let st: StackString = s.into();
+        assert_eq!(st, "Hello");
+        let sm: SmallString<20> = s.into();
+        assert!(sm.is_inline());
+        assert_eq!(sm, "Hello");
+        let s1: FixedString<5> = sm.try_into().unwrap();
+        assert_eq!(s1, s.as_str());
+        let s2: Result<FixedString<4>, _> = st.try_into();
+        assert!(s2.is_err());
+        let s3: String = s.into();
+        assert_eq!(s3, "Hello");
+
+        let long = "x".repeat(300);
+        let f = FixedString::<300>::try_from_str(&long).unwrap();
+        assert_eq!(f.capacity(), 300);
+        let sm: SmallString<300> = f.into();
+        assert!(sm.is_inline());
+        assert_eq!(sm, long);
+    }
+
+    #[cfg(all(feature = "bincode", feature = "borsh", feature = "postcard"))]
+    #[test]
+    fn test_binary_encodings() {
+        use postcard::experimental::max_size::MaxSize;
+
+        let config = bincode::config::standard();
+        let s = FixedString::<10>::try_from_str("hello").unwrap();
+        let bytes = bincode::encode_to_vec(s, config).unwrap();
+        let (t, _): (FixedString<10>, _) = bincode::decode_from_slice(&bytes, config).unwrap();
+        assert_eq!(t, s);
+        let bytes = bincode::encode_to_vec("hello there", config).unwrap();
+        assert!(bincode::decode_from_slice::<FixedString<10>, _>(&bytes, config).is_err());
+
+        let bytes = borsh::to_vec(&s).unwrap();
+        assert_eq!(borsh::from_slice::<FixedString<10>>(&bytes).unwrap(), s);
+        let bytes = borsh::to_vec("hello there").unwrap();
+        assert!(borsh::from_slice::<FixedString<10>>(&bytes).is_err());
+
+        assert_eq!(FixedString::<10>::POSTCARD_MAX_SIZE, 11);
+        assert_eq!(FixedString::<200>::POSTCARD_MAX_SIZE, 202);
+        let s = FixedString::<200>::try_from_str(&"é".repeat(100)).unwrap();
+        let mut buf = [0u8; FixedString::<200>::POSTCARD_MAX_SIZE];
+        let bytes = postcard::to_slice(&s, &mut buf).unwrap();
+        assert_eq!(bytes.len(), FixedString::<200>::POSTCARD_MAX_SIZE);
+        let t: FixedString<200> = postcard::from_bytes(bytes).unwrap();
+        assert_eq!(t, s);
+    }
+
+    #[test]
+    fn test_serde() {
+        use serde::Deserialize;
+
+        let s = FixedString::<30>::try_from_str("HELLO").unwrap();
+        let t = "HELLO";
+        let s = serde_json::to_vec(&s).unwrap();
+        let t = serde_json::to_vec(t).unwrap();
+        assert_eq!(s, t);
+
+        #[derive(Deserialize)]
+        struct A {
+            a: FixedString<5>,
+        }
+
+        let a: A = serde_json::from_str(r#"{"a": "b"}"#).unwrap();
+        assert_eq!(a.a, "b");
+
+        let e = serde_json::from_str::<A>(r#"{"a": "123456"}"#)
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "invalid length 6, expected a string of at most 5 bytes at line 1 column 14"
+        );
+    }
+
+    #[cfg(feature = "postgres_types")]
+    #[test]
+    fn test_from_sql() {
+        use postgres_types::{FromSql, Type};
+
+        let t = Type::TEXT;
+        let s = FixedString::<20>::from_sql(&t, b"Hello There").unwrap();
+        assert_eq!(s, "Hello There");
+        assert!(FixedString::<5>::from_sql(&t, b"Hello There").is_err());
+    }
+
+    #[cfg(feature = "async_graphql")]
+    #[test]
+    fn test_fixedstring_scalar() {
+        use async_graphql::{ScalarType, Value};
+
+        let s = FixedString::<5>::parse(Value::String("Hello".into())).unwrap();
+        assert_eq!(s, "Hello");
+        assert_eq!(s.to_value(), Value::String("Hello".into()));
+        assert!(FixedString::<4>::parse(Value::String("Hello".into())).is_err());
+        assert!(!FixedString::<4>::is_valid(&Value::String("Hello".into())));
+    }
+}
@@ -0,0 +1,322 @@
+//! Shared helpers for the `arbitrary`, `proptest` and `quickcheck` impls.
+//!
+//! Besides unconstrained strings these produce strings whose length is right
+//! around the inline capacity of a type, ending in a character of one to four
+//! bytes so that the boundary falls before, inside or just after it.
+
+use alloc::string::String;
+
+#[cfg(feature = "proptest")]
+use proptest::{prelude::*, sample::select};
+
+/// One character of each utf8 width.
+pub(crate) const BOUNDARY_CHARS: [char; 4] = ['a', 'é', '€', '🦀'];
+
+/// Build a string of `boundary + delta` bytes which ends with `last` and is
+/// otherwise padded with ascii.
+pub(crate) fn around_boundary(boundary: usize, delta: isize, last: char) -> String {
+    let len = boundary.saturating_add_signed(delta).max(last.len_utf8());
+    let mut s = String::with_capacity(len);
+    s.extend(core::iter::repeat_n('x', len - last.len_utf8()));
+    s.push(last);
+    s
+}
+
+/// Truncate `s` to at most `cap` bytes, dropping any partial character.
+pub(crate) fn truncate_to(mut s: String, cap: usize) -> String {
+    if s.len() > cap {
+        let mut index = cap;
+        while !s.is_char_boundary(index) {
+            index -= 1;
+        }
+        s.truncate(index);
+    }
+    s
+}
+
+#[cfg(feature = "arbitrary")]
+pub(crate) fn arbitrary_string(
+    u: &mut arbitrary::Unstructured<'_>,
+    boundaries: &[usize],
+) -> arbitrary::Result<String> {
+    if u.ratio(1, 2)? {
+        let boundary = *u.choose(boundaries)?;
+        let delta = u.int_in_range(-1..=1)?;
+        let last = *u.choose(&BOUNDARY_CHARS)?;
+        Ok(around_boundary(boundary, delta, last))
+    } else {
+        u.arbitrary()
+    }
+}
+
+#[cfg(feature = "proptest")]
+pub(crate) fn string_strategy(boundaries: &[usize]) -> impl Strategy<Value = String> + use<> {
+    prop_oneof![
+        any::<String>(),
+        (
+            select(boundaries.to_vec()),
+            -1..=1_isize,
+            select(&BOUNDARY_CHARS[..]),
+        )
+            .prop_map(|(boundary, delta, last)| around_boundary(boundary, delta, last)),
+    ]
+}
+
+#[cfg(feature = "quickcheck")]
+pub(crate) fn quickcheck_string(g: &mut quickcheck::Gen, boundaries: &[usize]) -> String {
+    use quickcheck::Arbitrary;
+
+    if <bool as Arbitrary>::arbitrary(g) {
+        let boundary = *g.choose(boundaries).unwrap();
+        let delta = *g.choose(&[-1, 0, 1]).unwrap();
+        let last = *g.choose(&BOUNDARY_CHARS).unwrap();
+        around_boundary(boundary, delta, last)
+    } else {
+        <String as Arbitrary>::arbitrary(g)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::{BOUNDARY_CHARS, around_boundary, truncate_to};
+
+    #[test]
+    fn test_around_boundary() {
+        for last in BOUNDARY_CHARS {
+            for delta in -1..=1 {
+                let s = around_boundary(24, delta, last);
+                assert_eq!(s.len() as isize, 24 + delta);
+                assert!(s.ends_with(last));
+            }
+        }
+        assert_eq!(around_boundary(0, -1, '🦀'), "🦀");
+    }
+
+    #[test]
+    fn test_truncate_to() {
+        let s = around_boundary(10, 1, '€');
+        assert_eq!(truncate_to(s.clone(), 11), s);
+        assert_eq!(truncate_to(s, 10), "x".repeat(8));
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        use crate::{FixedString, SmallCow, SmallString, StackCow, StackString};
+
+        let data: Vec<u8> = (0..=255).cycle().take(4096).collect();
+        let mut u = Unstructured::new(&data);
+        for _ in 0..16 {
+            let s = StackString::arbitrary(&mut u).unwrap();
+            assert_eq!(s.is_heap_allocated(), s.len() > crate::MAX_INLINE);
+            let s = SmallString::<8>::arbitrary(&mut u).unwrap();
+            assert_eq!(s.is_inline(), s.len() <= 8);
+            let s = FixedString::<8>::arbitrary(&mut u).unwrap();
+            assert!(s.len() <= 8);
+            StackCow::arbitrary(&mut u).unwrap();
+            SmallCow::<8>::arbitrary(&mut u).unwrap();
+        }
+    }
+
+    #[cfg(feature = "quickcheck")]
+    #[test]
+    fn test_quickcheck() {
+        use quickcheck::{Arbitrary, Gen};
+
+        use crate::{FixedString, SmallString, StackCow, StackString};
+
+        let mut g = Gen::new(64);
+        for _ in 0..16 {
+            let s = StackString::arbitrary(&mut g);
+            assert!(s.shrink().all(|t| t.len() <= s.len()));
+            let s = SmallString::<8>::arbitrary(&mut g);
+            assert_eq!(s.is_inline(), s.len() <= 8);
+            let s = FixedString::<8>::arbitrary(&mut g);
+            assert!(s.shrink().all(|t| t.len() <= 8));
+            let s = StackCow::arbitrary(&mut g);
+            assert!(s.is_owned());
+        }
+    }
+}
+
+#[cfg(all(test, feature = "proptest"))]
+mod proptests {
+    use proptest::{prelude::*, sample::select};
+    use std::{
+        collections::hash_map::DefaultHasher,
+        fmt::Debug,
+        hash::{Hash, Hasher},
+    };
+
+    use crate::{
+        FixedString, SmallCow, SmallString, StackCow, StackStr, StackString,
+        generators::BOUNDARY_CHARS,
+    };
+
+    #[derive(Clone, Debug)]
+    enum Op {
+        PushStr(String),
+        Push(char),
+        Pop,
+        Truncate(usize),
+        SplitOff(usize),
+        Clear,
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            any::<String>().prop_map(Op::PushStr),
+            select(&BOUNDARY_CHARS[..]).prop_map(Op::Push),
+            any::<char>().prop_map(Op::Push),
+            Just(Op::Pop),
+            any::<usize>().prop_map(Op::Truncate),
+            any::<usize>().prop_map(Op::SplitOff),
+            Just(Op::Clear),
+        ]
+    }
+
+    fn char_boundary(s: &str, index: usize) -> usize {
+        let mut index = index % (s.len() + 1);
+        while !s.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    }
+
+    /// Applies an `Op`, returning the popped or split off content.
+    trait Model {
+        fn apply(&mut self, op: &Op) -> Option<String>;
+    }
+
+    macro_rules! model {
+        ($t:ty, $s:ident => $e:expr) => {
+            impl Model for $t {
+                fn apply(&mut self, op: &Op) -> Option<String> {
+                    let $s = self;
+                    match op {
+                        Op::PushStr(x) => $e.push_str(x),
+                        Op::Push(c) => $e.push(*c),
+                        Op::Pop => return $e.pop().map(String::from),
+                        Op::Truncate(n) => $e.truncate(*n),
+                        Op::SplitOff(n) => return Some($e.split_off(*n).to_string()),
+                        Op::Clear => $e.clear(),
+                    }
+                    None
+                }
+            }
+        };
+    }
+
+    model!(String, s => s);
+    model!(StackString, s => s);
+    model!(SmallString<8>, s => s);
+    model!(SmallString<30>, s => s);
+    model!(StackCow<'_>, s => s.to_mut());
+    model!(SmallCow<'_, 8>, s => s.to_mut());
+    #[cfg(feature = "smart_string")]
+    model!(crate::SmartString, s => s);
+
+    fn hash_of<T: Hash + ?Sized>(t: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        t.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn check<T>(a: T, b: &T, ops: &[Op]) -> Result<(), TestCaseError>
+    where
+        T: StackStr + Model + Ord + Hash + Debug,
+    {
+        let mut model = String::from(StackStr::as_str(&a));
+        let other = StackStr::as_str(b);
+        prop_assert_eq!(a.cmp(b), model.as_str().cmp(other));
+        prop_assert_eq!(a == *b, model == other);
+        prop_assert_eq!(hash_of(&a), hash_of(model.as_str()));
+
+        let mut s = a;
+        for op in ops {
+            let op = match op {
+                Op::Truncate(n) => Op::Truncate(char_boundary(&model, *n)),
+                Op::SplitOff(n) => Op::SplitOff(char_boundary(&model, *n)),
+                op => op.clone(),
+            };
+            prop_assert_eq!(s.apply(&op), model.apply(&op));
+            prop_assert_eq!(StackStr::as_str(&s), model.as_str());
+            prop_assert_eq!(s.to_string(), model.clone());
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn stack_string_behaves_like_string(
+            a in any::<StackString>(),
+            b in any::<StackString>(),
+            ops in prop::collection::vec(op(), 0..16),
+        ) {
+            check(a, &b, &ops)?;
+        }
+
+        #[test]
+        fn small_string_behaves_like_string(
+            a in any::<SmallString<8>>(),
+            b in any::<SmallString<8>>(),
+            c in any::<SmallString<30>>(),
+            d in any::<SmallString<30>>(),
+            ops in prop::collection::vec(op(), 0..16),
+        ) {
+            check(a, &b, &ops)?;
+            check(c, &d, &ops)?;
+        }
+
+        #[test]
+        fn stack_cow_behaves_like_string(
+            a in any::<StackCow<'static>>(),
+            b in any::<StackCow<'static>>(),
+            ops in prop::collection::vec(op(), 0..16),
+        ) {
+            check(a, &b, &ops)?;
+        }
+
+        #[test]
+        fn small_cow_behaves_like_string(
+            a in any::<SmallCow<'static, 8>>(),
+            b in any::<SmallCow<'static, 8>>(),
+            ops in prop::collection::vec(op(), 0..16),
+        ) {
+            check(a, &b, &ops)?;
+        }
+
+        #[cfg(feature = "smart_string")]
+        #[test]
+        fn smart_string_behaves_like_string(
+            a in any::<crate::SmartString>(),
+            b in any::<crate::SmartString>(),
+            ops in prop::collection::vec(op(), 0..16),
+        ) {
+            check(a, &b, &ops)?;
+        }
+
+        #[test]
+        fn borrowed_cow_behaves_like_string(
+            a in any::<String>(),
+            b in any::<String>(),
+            ops in prop::collection::vec(op(), 0..16),
+        ) {
+            let borrowed = StackCow::from(b.as_str());
+            check(StackCow::from(a.as_str()), &borrowed, &ops)?;
+        }
+
+        #[test]
+        fn fixed_string_fits(s in any::<FixedString<10>>()) {
+            prop_assert!(s.len() <= 10);
+            prop_assert_eq!(FixedString::<10>::try_from_str(s.as_str()), Ok(s));
+        }
+
+        #[test]
+        fn small_string_is_inline_when_it_fits(s in any::<SmallString<8>>()) {
+            prop_assert_eq!(s.is_inline(), s.len() <= 8);
+        }
+    }
+}
@@ -0,0 +1,704 @@
+use arrayvec::{ArrayString, CapacityError};
+use core::{
+    borrow::{Borrow, BorrowMut},
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    mem,
+    ops::{Deref, DerefMut},
+    str,
+};
+
+/// Largest length stored directly in the length byte, the values 254 and 255
+/// are reserved as niches.  An `InlineString` with a larger `CAP` marks
+/// contents of `MAX_CAP` bytes or more with a length byte of `MAX_CAP` and
+/// fills the unused tail of the buffer with `0xff`, which never occurs in
+/// utf8, so the length is found with a binary search.
+pub const MAX_CAP: usize = 253;
+
+/// Byte used to pad the unused tail of the buffer of long contents.
+const PADDING: u8 = 0xff;
+
+/// Length of an `InlineString`. Only the values `0..=253` are valid, which
+/// leaves 254 and 255 free for the compiler to use as the discriminant of
+/// `SmallString`.
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum InlineLen {
+    L0 = 0,
+    L1,
+    L2,
+    L3,
+    L4,
+    L5,
+    L6,
+    L7,
+    L8,
+    L9,
+    L10,
+    L11,
+    L12,
+    L13,
+    L14,
+    L15,
+    L16,
+    L17,
+    L18,
+    L19,
+    L20,
+    L21,
+    L22,
+    L23,
+    L24,
+    L25,
+    L26,
+    L27,
+    L28,
+    L29,
+    L30,
+    L31,
+    L32,
+    L33,
+    L34,
+    L35,
+    L36,
+    L37,
+    L38,
+    L39,
+    L40,
+    L41,
+    L42,
+    L43,
+    L44,
+    L45,
+    L46,
+    L47,
+    L48,
+    L49,
+    L50,
+    L51,
+    L52,
+    L53,
+    L54,
+    L55,
+    L56,
+    L57,
+    L58,
+    L59,
+    L60,
+    L61,
+    L62,
+    L63,
+    L64,
+    L65,
+    L66,
+    L67,
+    L68,
+    L69,
+    L70,
+    L71,
+    L72,
+    L73,
+    L74,
+    L75,
+    L76,
+    L77,
+    L78,
+    L79,
+    L80,
+    L81,
+    L82,
+    L83,
+    L84,
+    L85,
+    L86,
+    L87,
+    L88,
+    L89,
+    L90,
+    L91,
+    L92,
+    L93,
+    L94,
+    L95,
+    L96,
+    L97,
+    L98,
+    L99,
+    L100,
+    L101,
+    L102,
+    L103,
+    L104,
+    L105,
+    L106,
+    L107,
+    L108,
+    L109,
+    L110,
+    L111,
+    L112,
+    L113,
+    L114,
+    L115,
+    L116,
+    L117,
+    L118,
+    L119,
+    L120,
+    L121,
+    L122,
+    L123,
+    L124,
+    L125,
+    L126,
+    L127,
+    L128,
+    L129,
+    L130,
+    L131,
+    L132,
+    L133,
+    L134,
+    L135,
+    L136,
+    L137,
+    L138,
+    L139,
+    L140,
+    L141,
+    L142,
+    L143,
+    L144,
+    L145,
+    L146,
+    L147,
+    L148,
+    L149,
+    L150,
+    L151,
+    L152,
+    L153,
+    L154,
+    L155,
+    L156,
+    L157,
+    L158,
+    L159,
+    L160,
+    L161,
+    L162,
+    L163,
+    L164,
+    L165,
+    L166,
+    L167,
+    L168,
+    L169,
+    L170,
+    L171,
+    L172,
+    L173,
+    L174,
+    L175,
+    L176,
+    L177,
+    L178,
+    L179,
+    L180,
+    L181,
+    L182,
+    L183,
+    L184,
+    L185,
+    L186,
+    L187,
+    L188,
+    L189,
+    L190,
+    L191,
+    L192,
+    L193,
+    L194,
+    L195,
+    L196,
+    L197,
+    L198,
+    L199,
+    L200,
+    L201,
+    L202,
+    L203,
+    L204,
+    L205,
+    L206,
+    L207,
+    L208,
+    L209,
+    L210,
+    L211,
+    L212,
+    L213,
+    L214,
+    L215,
+    L216,
+    L217,
+    L218,
+    L219,
+    L220,
+    L221,
+    L222,
+    L223,
+    L224,
+    L225,
+    L226,
+    L227,
+    L228,
+    L229,
+    L230,
+    L231,
+    L232,
+    L233,
+    L234,
+    L235,
+    L236,
+    L237,
+    L238,
+    L239,
+    L240,
+    L241,
+    L242,
+    L243,
+    L244,
+    L245,
+    L246,
+    L247,
+    L248,
+    L249,
+    L250,
+    L251,
+    L252,
+    L253,
+}
+
+impl InlineLen {
+    #[inline]
+    const fn new(len: usize) -> Self {
+        assert!(len <= MAX_CAP);
+        // Safe since every value in 0..=MAX_CAP is a variant of InlineLen
+        #[allow(clippy::cast_possible_truncation)]
+        unsafe {
+            mem::transmute::<u8, Self>(len as u8)
+        }
+    }
+
+    #[inline]
+    fn get(self) -> usize {
+        self as usize
+    }
+}
+
+/// A string stored in a `CAP` byte buffer followed by a single length byte,
+/// so that `size_of::<InlineString<CAP>>() == CAP + 1`.
+///
+/// This is the inline representation of `SmallString`, the length byte is
+/// last and provides a niche which lets `SmallString<CAP>` be no larger than
+/// `max(CAP + 1, size_of::<String>())` rounded up to the alignment of
+/// `String`.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct InlineString<const CAP: usize> {
+    buf: [u8; CAP],
+    len: InlineLen,
+}
+
+impl<const CAP: usize> InlineString<CAP> {
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; CAP],
+            len: InlineLen::L0,
+        }
+    }
+
+    /// Create an `InlineString` from a `&str` in a const context
+    ///
+    /// # Panics
+    /// Panics if the string is longer than `CAP`
+    #[must_use]
+    pub const fn from_str_const(s: &str) -> Self {
+        let mut a = Self::new();
+        let bytes = s.as_bytes();
+        assert!(bytes.len() <= CAP);
+        let mut i = 0;
+        while i < bytes.len() {
+            a.buf[i] = bytes[i];
+            i += 1;
+        }
+        if bytes.len() < MAX_CAP {
+            a.len = InlineLen::new(bytes.len());
+        } else {
+            while i < CAP {
+                a.buf[i] = PADDING;
+                i += 1;
+            }
+            a.len = InlineLen::L253;
+        }
+        a
+    }
+
+    /// Create an `InlineString` from a `&str`
+    /// # Errors
+    ///
+    /// Will return an Error if the string is longer than `CAP`
+    pub fn from(s: &str) -> Result<Self, CapacityError<&str>> {
+        let mut a = Self::new();
+        a.try_push_str(s)?;
+        Ok(a)
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        CAP
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        let len = self.len.get();
+        if CAP > MAX_CAP && len == MAX_CAP {
+            MAX_CAP + self.buf[MAX_CAP..].partition_point(|b| *b != PADDING)
+        } else {
+            len
+        }
+    }
+
+    /// Set the length, `buf[..new_len]` must be valid utf8 and `buf[..len]`
+    /// must hold the previous contents.
+    fn set_len(&mut self, new_len: usize) {
+        if CAP > MAX_CAP && new_len >= MAX_CAP {
+            let old_len = self.len();
+            if self.len.get() == MAX_CAP {
+                if new_len < old_len {
+                    self.buf[new_len..old_len].fill(PADDING);
+                }
+            } else {
+                self.buf[new_len..].fill(PADDING);
+            }
+            self.len = InlineLen::L253;
+        } else {
+            self.len = InlineLen::new(new_len);
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn remaining_capacity(&self) -> usize {
+        CAP - self.len()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        // Safe since buf[..len] is always valid utf8
+        unsafe { str::from_utf8_unchecked(&self.buf[..self.len()]) }
+    }
+
+    #[inline]
+    pub fn as_mut_str(&mut self) -> &mut str {
+        let len = self.len();
+        // Safe since buf[..len] is always valid utf8
+        unsafe { str::from_utf8_unchecked_mut(&mut self.buf[..len]) }
+    }
+
+    /// Append a `&str`, leaving the string unchanged if it doesn't fit
+    /// # Errors
+    ///
+    /// Will return an Error if the resulting string would be longer than `CAP`
+    pub fn try_push_str<'a>(&mut self, s: &'a str) -> Result<(), CapacityError<&'a str>> {
+        let len = self.len();
+        let new_len = len + s.len();
+        if new_len > CAP {
+            return Err(CapacityError::new(s));
+        }
+        self.buf[len..new_len].copy_from_slice(s.as_bytes());
+        self.set_len(new_len);
+        Ok(())
+    }
+
+    /// Append a `char`, leaving the string unchanged if it doesn't fit
+    /// # Errors
+    ///
+    /// Will return an Error if the resulting string would be longer than `CAP`
+    pub fn try_push(&mut self, c: char) -> Result<(), CapacityError<char>> {
+        let mut bits = [0; 4];
+        self.try_push_str(c.encode_utf8(&mut bits))
+            .map_err(|_| CapacityError::new(c))
+    }
+
+    /// # Panics
+    /// Panics if the resulting string would be longer than `CAP`
+    pub fn push_str(&mut self, s: &str) {
+        self.try_push_str(s).unwrap();
+    }
+
+    /// # Panics
+    /// Panics if the resulting string would be longer than `CAP`
+    pub fn push(&mut self, c: char) {
+        self.try_push(c).unwrap();
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.as_str().chars().next_back()?;
+        self.set_len(self.len() - c.len_utf8());
+        Some(c)
+    }
+
+    /// # Panics
+    /// Panics if `new_len` does not lie on a char boundary.
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len < self.len() {
+            assert!(self.as_str().is_char_boundary(new_len));
+            self.set_len(new_len);
+        }
+    }
+
+    /// # Panics
+    /// Panics if `idx` is out of bounds or does not lie on a char boundary.
+    pub fn remove(&mut self, idx: usize) -> char {
+        let c = self.as_str()[idx..].chars().next().unwrap();
+        let len = self.len();
+        let next = idx + c.len_utf8();
+        self.buf.copy_within(next..len, idx);
+        self.set_len(len - (next - idx));
+        c
+    }
+
+    pub fn clear(&mut self) {
+        self.len = InlineLen::L0;
+    }
+}
+
+impl<const CAP: usize> Default for InlineString<CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const CAP: usize> Deref for InlineString<CAP> {
+    type Target = str;
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl<const CAP: usize> DerefMut for InlineString<CAP> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_str()
+    }
+}
+
+impl<const CAP: usize> Borrow<str> for InlineString<CAP> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const CAP: usize> BorrowMut<str> for InlineString<CAP> {
+    fn borrow_mut(&mut self) -> &mut str {
+        self.as_mut_str()
+    }
+}
+
+impl<const CAP: usize> AsRef<str> for InlineString<CAP> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const CAP: usize> PartialEq for InlineString<CAP> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const CAP: usize> Eq for InlineString<CAP> {}
+
+impl<const CAP: usize> PartialOrd for InlineString<CAP> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const CAP: usize> Ord for InlineString<CAP> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const CAP: usize> Hash for InlineString<CAP> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl<const CAP: usize> PartialEq<str> for InlineString<CAP> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const CAP: usize> PartialEq<&str> for InlineString<CAP> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const CAP: usize> fmt::Debug for InlineString<CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const CAP: usize> fmt::Display for InlineString<CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const CAP: usize> fmt::Write for InlineString<CAP> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.try_push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const CAP: usize> From<ArrayString<CAP>> for InlineString<CAP> {
+    fn from(item: ArrayString<CAP>) -> Self {
+        let mut s = Self::new();
+        s.push_str(&item);
+        s
+    }
+}
+
+impl<const CAP: usize> From<InlineString<CAP>> for ArrayString<CAP> {
+    fn from(item: InlineString<CAP>) -> Self {
+        let mut s = Self::new();
+        s.push_str(&item);
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::inline_string::InlineString;
+
+    #[test]
+    fn test_sizeof() {
+        assert_eq!(core::mem::size_of::<InlineString<0>>(), 1);
+        assert_eq!(core::mem::size_of::<InlineString<30>>(), 31);
+        assert_eq!(core::mem::size_of::<InlineString<253>>(), 254);
+        assert_eq!(core::mem::size_of::<InlineString<300>>(), 301);
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut s = InlineString::<5>::new();
+        s.push_str("ab");
+        s.push('\u{e9}');
+        assert_eq!(s, "ab\u{e9}");
+        assert_eq!(s.len(), 4);
+        assert!(s.try_push('\u{e9}').is_err());
+        assert!(s.try_push_str("cd").is_err());
+        s.push('c');
+        assert_eq!(s.remaining_capacity(), 0);
+        assert_eq!(s.pop(), Some('c'));
+        assert_eq!(s.pop(), Some('\u{e9}'));
+        assert_eq!(s, "ab");
+        s.clear();
+        assert!(s.is_empty());
+        assert_eq!(s.pop(), None);
+    }
+
+    #[test]
+    fn test_remove_truncate() {
+        let mut s = InlineString::<10>::from("h\u{e9}llo").unwrap();
+        assert_eq!(s.remove(1), '\u{e9}');
+        assert_eq!(s, "hllo");
+        s.truncate(2);
+        assert_eq!(s, "hl");
+        s.truncate(5);
+        assert_eq!(s, "hl");
+        assert!(InlineString::<3>::from("hello").is_err());
+    }
+
+    #[test]
+    fn test_from_str_const() {
+        const S: InlineString<10> = InlineString::from_str_const("h\u{e9}llo");
+        assert_eq!(S, "h\u{e9}llo");
+        assert_eq!(S.len(), 6);
+    }
+
+    #[test]
+    fn test_large_capacity() {
+        let long = "x".repeat(260);
+        let mut s = InlineString::<300>::from(&long).unwrap();
+        assert_eq!(s.len(), 260);
+        assert_eq!(s, long.as_str());
+        s.push_str(&"\u{e9}".repeat(20));
+        assert_eq!(s.len(), 300);
+        assert_eq!(s.remaining_capacity(), 0);
+        assert!(s.try_push('a').is_err());
+        assert_eq!(s.pop(), Some('\u{e9}'));
+        assert_eq!(s.len(), 298);
+        assert_eq!(s.remove(0), 'x');
+        assert_eq!(s.len(), 297);
+        s.truncate(254);
+        assert_eq!(s.len(), 254);
+        s.truncate(10);
+        assert_eq!(s, "x".repeat(10).as_str());
+        s.push_str(&long);
+        assert_eq!(s.len(), 270);
+        assert!(s.ends_with(&long));
+        s.clear();
+        assert!(s.is_empty());
+        assert!(InlineString::<300>::from(&"x".repeat(301)).is_err());
+
+        const S: InlineString<256> = InlineString::from_str_const(
+            "0123456789012345678901234567890123456789012345678901234567890123456789\
+             0123456789012345678901234567890123456789012345678901234567890123456789\
+             0123456789012345678901234567890123456789012345678901234567890123456789\
+             0123456789012345678901234567890123456789012345",
+        );
+        assert_eq!(S.len(), 256);
+        assert!(S.ends_with("45"));
+    }
+
+    #[test]
+    fn test_arraystring_conversion() {
+        use arrayvec::ArrayString;
+
+        let a = ArrayString::<300>::from(&"\u{e9}".repeat(150)).unwrap();
+        let s: InlineString<300> = a.into();
+        assert_eq!(s, a.as_str());
+        let b: ArrayString<300> = s.into();
+        assert_eq!(b, a);
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut a = InlineString::<10>::from("abc").unwrap();
+        let b = InlineString::<10>::from("ab").unwrap();
+        assert!(b < a);
+        a.truncate(2);
+        assert_eq!(a, b);
+    }
+}
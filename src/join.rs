@@ -0,0 +1,124 @@
+use core::fmt::Write;
+
+use crate::{SmallString, StackString};
+
+/// Join and concatenate slices of strings into the crate's string types, the
+/// total length is computed first so the result is allocated at most once.
+///
+/// The methods have distinct names since `[T]::join` and `[T]::concat` would
+/// otherwise take precedence.
+pub trait StackJoin {
+    fn concat_sstr(&self) -> StackString;
+
+    fn join_sstr(&self, sep: &str) -> StackString;
+
+    fn concat_small<const CAP: usize>(&self) -> SmallString<CAP>;
+
+    fn join_small<const CAP: usize>(&self, sep: &str) -> SmallString<CAP>;
+}
+
+impl<S: AsRef<str>> StackJoin for [S] {
+    fn concat_sstr(&self) -> StackString {
+        StackString::concat(self)
+    }
+
+    fn join_sstr(&self, sep: &str) -> StackString {
+        StackString::join(self, sep)
+    }
+
+    fn concat_small<const CAP: usize>(&self) -> SmallString<CAP> {
+        SmallString::concat(self)
+    }
+
+    fn join_small<const CAP: usize>(&self, sep: &str) -> SmallString<CAP> {
+        SmallString::join(self, sep)
+    }
+}
+
+pub(crate) fn joined_len<I, T>(parts: I, sep: &str) -> usize
+where
+    I: Iterator<Item = T>,
+    T: AsRef<str>,
+{
+    let (count, len) = parts.fold((0_usize, 0_usize), |(count, len), s| {
+        (count + 1, len + s.as_ref().len())
+    });
+    len + sep.len() * count.saturating_sub(1)
+}
+
+pub(crate) fn push_joined<W, I, T>(buf: &mut W, parts: I, sep: &str)
+where
+    W: Write,
+    I: Iterator<Item = T>,
+    T: AsRef<str>,
+{
+    for (i, s) in parts.enumerate() {
+        if i > 0 {
+            buf.write_str(sep).unwrap();
+        }
+        buf.write_str(s.as_ref()).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SmallString, StackJoin, StackString, concat_sstr};
+
+    #[test]
+    fn test_concat() {
+        let s = StackString::concat(&["a", "b", "c"]);
+        assert_eq!(s, "abc");
+        assert!(s.is_inline());
+        let s = StackString::concat(&["hello there ", "this is a ", "long string"]);
+        assert_eq!(s, "hello there this is a long string");
+        assert_eq!(s.capacity(), s.len());
+
+        let s = SmallString::<5>::concat(&["ab", "cd"]);
+        assert!(s.is_inline());
+        let s = SmallString::<5>::concat(&["abc", "def"]);
+        assert!(s.is_boxed());
+        assert_eq!(s, "abcdef");
+        assert_eq!(s.capacity(), 6);
+
+        let empty: [&str; 0] = [];
+        assert_eq!(StackString::concat(&empty), "");
+    }
+
+    #[test]
+    fn test_concat_sstr() {
+        let a = StackString::from("a");
+        let b = String::from("b");
+        let c = SmallString::<5>::from("c");
+        let s = concat_sstr!(a, b, c, "d");
+        assert_eq!(s, "abcd");
+        assert_eq!(concat_sstr!(), "");
+    }
+
+    #[test]
+    fn test_join() {
+        let parts = ["usr", "local", "bin"];
+        let s = SmallString::<20>::join(parts, "/");
+        assert_eq!(s, "usr/local/bin");
+        assert!(s.is_inline());
+        let s = SmallString::<5>::join(parts.iter(), "/");
+        assert_eq!(s, "usr/local/bin");
+        assert_eq!(s.capacity(), 13);
+        let s = StackString::join(&parts, ", ");
+        assert_eq!(s, "usr, local, bin");
+    }
+
+    #[test]
+    fn test_stack_join() {
+        let parts: Vec<StackString> = ["a", "b"].into_iter().map(Into::into).collect();
+        assert_eq!(parts.concat_sstr(), "ab");
+        assert_eq!(parts.join_sstr("-"), "a-b");
+        let s: SmallString<3> = parts.join_small("-");
+        assert!(s.is_inline());
+        let s = parts.join_small::<2>("-");
+        assert!(s.is_boxed());
+        assert_eq!(parts.concat_small::<2>(), "ab");
+
+        let parts = [String::from("x"), String::from("y")];
+        assert_eq!(parts.join_sstr("+"), "x+y");
+    }
+}
@@ -1,19 +1,44 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![allow(clippy::too_many_lines)]
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::similar_names)]
 #![allow(clippy::used_underscore_binding)]
 #![allow(clippy::unsafe_derive_deserialize)]
 
+extern crate alloc;
+
+#[cfg(any(feature = "bincode", feature = "borsh", feature = "postcard"))]
+mod binary;
+mod boxed_string;
+pub mod fixed_string;
+#[cfg(any(feature = "arbitrary", feature = "proptest", feature = "quickcheck"))]
+mod generators;
+pub mod inline_string;
+pub mod join;
+#[cfg(feature = "prost")]
+pub mod prost_encoding;
+pub mod serde_adapters;
+pub mod small_cow;
 pub mod small_string;
 pub mod stack_cow;
+pub mod stack_str;
 pub mod stack_string;
 
 #[cfg(feature = "smart_string")]
 pub mod smart_string;
 
-pub const MAX_INLINE: usize = std::mem::size_of::<String>();
+pub const MAX_INLINE: usize = core::mem::size_of::<alloc::string::String>();
 
-pub use crate::{small_string::SmallString, stack_cow::StackCow, stack_string::StackString};
+pub use crate::{
+    fixed_string::{CapacityError, FixedString},
+    inline_string::InlineString,
+    join::StackJoin,
+    small_cow::SmallCow,
+    small_string::SmallString,
+    stack_cow::StackCow,
+    stack_str::StackStr,
+    stack_string::StackString,
+};
 
 #[cfg(feature = "smart_string")]
 pub use crate::smart_string::SmartString;
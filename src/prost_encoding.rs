@@ -0,0 +1,287 @@
+//! Field encoding functions for using `StackString` and `SmallString<CAP>` as
+//! protobuf `string` fields.
+//!
+//! These mirror `prost::encoding::string` and are meant to be called from a
+//! `prost::Message` implementation in place of the `String` versions, the
+//! wire format is identical.  Decoding converts straight from the input
+//! buffer, so strings which fit inline never allocate.
+
+use alloc::{string::String, vec::Vec};
+use core::str;
+use prost::{
+    DecodeError,
+    bytes::{Buf, BufMut},
+    encoding::{
+        DecodeContext, WireType, check_wire_type, decode_varint, encode_key, encode_varint,
+        encoded_len_varint, key_len, string,
+    },
+};
+
+/// A length delimited string field which runs past the end of the buffer.
+const UNDERFLOW: &[u8] = &[1];
+/// A length delimited string field which is not utf8.
+const NOT_UTF8: &[u8] = &[1, 0xff];
+
+/// prost only builds `DecodeError` internally (`DecodeError::new` is
+/// deprecated), so get the error by handing `field` to prost's own string
+/// decoder, which also gives the same error kind it would have returned.
+#[cold]
+fn decode_error(mut field: &[u8]) -> DecodeError {
+    string::merge(
+        WireType::LengthDelimited,
+        &mut String::new(),
+        &mut field,
+        DecodeContext::default(),
+    )
+    .expect_err("field is invalid")
+}
+
+/// Encode `value` as a length delimited string field with the given tag.
+pub fn encode<T: AsRef<str>>(tag: u32, value: &T, buf: &mut impl BufMut) {
+    let value = value.as_ref();
+    encode_key(tag, WireType::LengthDelimited, buf);
+    encode_varint(value.len() as u64, buf);
+    buf.put_slice(value.as_bytes());
+}
+
+/// Encode each of `values` as a separate string field with the given tag.
+pub fn encode_repeated<T: AsRef<str>>(tag: u32, values: &[T], buf: &mut impl BufMut) {
+    for value in values {
+        encode(tag, value, buf);
+    }
+}
+
+/// Replace `value` with the next length delimited string in `buf`, on
+/// failure `value` is left empty as with `prost::encoding::string::merge`.
+///
+/// # Errors
+/// Returns an error if the wire type is wrong, the buffer is too short or the
+/// contents are not utf8.
+pub fn merge<T>(
+    wire_type: WireType,
+    value: &mut T,
+    buf: &mut impl Buf,
+    _ctx: DecodeContext,
+) -> Result<(), DecodeError>
+where
+    T: for<'a> From<&'a str> + From<String>,
+{
+    decode(wire_type, buf)
+        .map(|v| *value = v)
+        .inspect_err(|_| *value = T::from(""))
+}
+
+fn decode<T>(wire_type: WireType, buf: &mut impl Buf) -> Result<T, DecodeError>
+where
+    T: for<'a> From<&'a str> + From<String>,
+{
+    check_wire_type(WireType::LengthDelimited, wire_type)?;
+    let len = decode_varint(buf)?;
+    if len > buf.remaining() as u64 {
+        return Err(decode_error(UNDERFLOW));
+    }
+    let len = usize::try_from(len).map_err(|_| decode_error(UNDERFLOW))?;
+    let invalid = |_| decode_error(NOT_UTF8);
+
+    if let Some(bytes) = buf.chunk().get(..len) {
+        let value = str::from_utf8(bytes).map(T::from).map_err(invalid)?;
+        buf.advance(len);
+        Ok(value)
+    } else {
+        let mut bytes = Vec::with_capacity(len);
+        bytes.put(buf.take(len));
+        String::from_utf8(bytes)
+            .map(T::from)
+            .map_err(|e| invalid(e.utf8_error()))
+    }
+}
+
+/// Append the next length delimited string in `buf` to `values`.
+///
+/// # Errors
+/// Returns an error if the field can't be decoded, see [`merge`].
+pub fn merge_repeated<T>(
+    wire_type: WireType,
+    values: &mut Vec<T>,
+    buf: &mut impl Buf,
+    ctx: DecodeContext,
+) -> Result<(), DecodeError>
+where
+    T: for<'a> From<&'a str> + From<String>,
+{
+    check_wire_type(WireType::LengthDelimited, wire_type)?;
+    let mut value = T::from("");
+    merge(wire_type, &mut value, buf, ctx)?;
+    values.push(value);
+    Ok(())
+}
+
+/// Number of bytes [`encode`] writes for `value`, including the key.
+pub fn encoded_len<T: AsRef<str>>(tag: u32, value: &T) -> usize {
+    let len = value.as_ref().len();
+    key_len(tag) + encoded_len_varint(len as u64) + len
+}
+
+/// Number of bytes [`encode_repeated`] writes for `values`.
+pub fn encoded_len_repeated<T: AsRef<str>>(tag: u32, values: &[T]) -> usize {
+    values.iter().map(|value| encoded_len(tag, value)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::{
+        DecodeError, Message,
+        bytes::{Buf, BufMut},
+        encoding::{DecodeContext, WireType, skip_field, string, uint32},
+    };
+
+    use crate::{SmallString, StackString};
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Person {
+        id: u32,
+        name: StackString,
+        nickname: SmallString<16>,
+        emails: Vec<SmallString<8>>,
+    }
+
+    impl Message for Person {
+        fn encode_raw(&self, buf: &mut impl BufMut) {
+            if self.id != 0 {
+                uint32::encode(1, &self.id, buf);
+            }
+            if !self.name.is_empty() {
+                crate::prost_encoding::encode(2, &self.name, buf);
+            }
+            if !self.nickname.is_empty() {
+                crate::prost_encoding::encode(3, &self.nickname, buf);
+            }
+            crate::prost_encoding::encode_repeated(4, &self.emails, buf);
+        }
+
+        fn merge_field(
+            &mut self,
+            tag: u32,
+            wire_type: WireType,
+            buf: &mut impl Buf,
+            ctx: DecodeContext,
+        ) -> Result<(), DecodeError> {
+            match tag {
+                1 => uint32::merge(wire_type, &mut self.id, buf, ctx),
+                2 => crate::prost_encoding::merge(wire_type, &mut self.name, buf, ctx),
+                3 => crate::prost_encoding::merge(wire_type, &mut self.nickname, buf, ctx),
+                4 => crate::prost_encoding::merge_repeated(wire_type, &mut self.emails, buf, ctx),
+                _ => skip_field(wire_type, tag, buf, ctx),
+            }
+        }
+
+        fn encoded_len(&self) -> usize {
+            (if self.id == 0 {
+                0
+            } else {
+                uint32::encoded_len(1, &self.id)
+            }) + (if self.name.is_empty() {
+                0
+            } else {
+                crate::prost_encoding::encoded_len(2, &self.name)
+            }) + (if self.nickname.is_empty() {
+                0
+            } else {
+                crate::prost_encoding::encoded_len(3, &self.nickname)
+            }) + crate::prost_encoding::encoded_len_repeated(4, &self.emails)
+        }
+
+        fn clear(&mut self) {
+            *self = Self::default();
+        }
+    }
+
+    fn person() -> Person {
+        Person {
+            id: 42,
+            name: "a name which is too long to be inline".into(),
+            nickname: "ferris".into(),
+            emails: vec!["a@b.c".into(), "somebody@example.com".into()],
+        }
+    }
+
+    #[test]
+    fn test_prost_round_trip() {
+        let p = person();
+        let buf = p.encode_to_vec();
+        assert_eq!(buf.len(), p.encoded_len());
+
+        let decoded = Person::decode(buf.as_slice()).unwrap();
+        assert_eq!(decoded, p);
+        assert!(decoded.name.is_heap_allocated());
+        assert!(decoded.nickname.is_inline());
+        assert!(decoded.emails[0].is_inline());
+        assert!(!decoded.emails[1].is_inline());
+
+        let empty = Person::decode(&[][..]).unwrap();
+        assert_eq!(empty, Person::default());
+    }
+
+    #[test]
+    fn test_prost_wire_format() {
+        let mut expected = Vec::new();
+        string::encode(3, &String::from("ferris"), &mut expected);
+
+        let mut buf = Vec::new();
+        crate::prost_encoding::encode(3, &SmallString::<16>::from("ferris"), &mut buf);
+        assert_eq!(buf, expected);
+        assert_eq!(
+            crate::prost_encoding::encoded_len(3, &StackString::from("ferris")),
+            string::encoded_len(3, &String::from("ferris"))
+        );
+    }
+
+    #[test]
+    fn test_prost_split_buffer() {
+        let p = person();
+        let buf = p.encode_to_vec();
+        for index in 0..buf.len() {
+            let (head, tail) = buf.split_at(index);
+            assert_eq!(Person::decode(head.chain(tail)).unwrap(), p);
+        }
+    }
+
+    #[test]
+    fn test_prost_errors() {
+        let mut buf = Vec::new();
+        string::encode(3, &String::from("ferris"), &mut buf);
+        let e = Person::decode(&buf[..buf.len() - 1]).unwrap_err();
+        assert!(e.to_string().contains("buffer underflow"));
+
+        let last = buf.len() - 1;
+        buf[last] = 0xff;
+        let e = Person::decode(buf.as_slice()).unwrap_err();
+        assert!(e.to_string().contains("not UTF-8"));
+
+        let mut buf = Vec::new();
+        uint32::encode(2, &7, &mut buf);
+        assert!(Person::decode(buf.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_prost_merge_clears_on_error() {
+        let mut buf = Vec::new();
+        string::encode(3, &String::from("ab\u{e9}"), &mut buf);
+        let last = buf.len() - 1;
+        buf[last] = 0xff;
+
+        let mut p = person();
+        assert!(p.merge(buf.as_slice()).is_err());
+        assert_eq!(p.nickname, "");
+
+        let mut name = StackString::from("a name");
+        let e = crate::prost_encoding::merge(
+            WireType::Varint,
+            &mut name,
+            &mut &buf[..],
+            DecodeContext::default(),
+        );
+        assert!(e.is_err());
+        assert!(name.is_empty());
+    }
+}
@@ -0,0 +1,393 @@
+//! Adapters for `#[serde(deserialize_with = "...")]` which validate or
+//! normalize a string while it is being deserialized.
+//!
+//! Every adapter works on the borrowed input and builds the final value
+//! directly, so short results stay inline and long ones are allocated once
+//! at their final size.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use stack_string::{SmallString, StackString};
+//!
+//! #[derive(Deserialize)]
+//! struct User {
+//!     #[serde(deserialize_with = "stack_string::serde_adapters::max_len::reject::<16, _, _>")]
+//!     login: SmallString<16>,
+//!     #[serde(deserialize_with = "stack_string::serde_adapters::trim::deserialize")]
+//!     name: StackString,
+//!     #[serde(deserialize_with = "stack_string::serde_adapters::lowercase::deserialize")]
+//!     email: StackString,
+//! }
+//!
+//! let user: User = serde_json::from_str(
+//!     r#"{"login": "ferris", "name": "  Ferris ", "email": "Ferris@Example.com"}"#,
+//! )
+//! .unwrap();
+//! assert_eq!(user.login, "ferris");
+//! assert_eq!(user.name, "Ferris");
+//! assert_eq!(user.email, "ferris@example.com");
+//! ```
+
+use alloc::string::String;
+use arrayvec::ArrayString;
+use core::{
+    fmt::{self, Write},
+    marker::PhantomData,
+};
+use serde::de::{Deserializer, Error, Visitor};
+
+use crate::StackStr;
+
+/// Results up to this length are formatted on the stack before being
+/// converted.
+const SCRATCH_SIZE: usize = 256;
+
+/// Turns the input into the output value, or fails.
+trait Transform {
+    fn apply<T, E>(self, s: &str) -> Result<T, E>
+    where
+        T: StackStr + From<String>,
+        E: Error;
+}
+
+struct TransformVisitor<T, X>(X, PhantomData<T>);
+
+impl<T, X> Visitor<'_> for TransformVisitor<T, X>
+where
+    T: StackStr + From<String>,
+    X: Transform,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.0.apply(v)
+    }
+}
+
+fn deserialize_with<'de, D, T, X>(deserializer: D, transform: X) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: StackStr + From<String>,
+    X: Transform,
+{
+    deserializer.deserialize_str(TransformVisitor(transform, PhantomData))
+}
+
+struct LengthCounter(usize);
+
+impl Write for LengthCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// Build a `T` from `value`, formatting it into a stack buffer or into a
+/// `String` of exactly the right capacity.
+fn build<T>(value: impl fmt::Display) -> T
+where
+    T: StackStr + From<String>,
+{
+    let mut counter = LengthCounter(0);
+    write!(counter, "{value}").unwrap();
+    if counter.0 <= SCRATCH_SIZE {
+        let mut buf = ArrayString::<SCRATCH_SIZE>::new();
+        write!(buf, "{value}").unwrap();
+        T::from_str_lossless(&buf)
+    } else {
+        let mut buf = String::with_capacity(counter.0);
+        write!(buf, "{value}").unwrap();
+        T::from(buf)
+    }
+}
+
+/// Limit the length in bytes of a string.
+pub mod max_len {
+    use alloc::string::String;
+    use core::fmt;
+    use serde::de::{Deserializer, Error, Expected};
+
+    use super::{Transform, deserialize_with};
+    use crate::StackStr;
+
+    struct Reject(usize);
+
+    impl Expected for Reject {
+        fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "a string of at most {} bytes", self.0)
+        }
+    }
+
+    impl Transform for Reject {
+        fn apply<T, E>(self, s: &str) -> Result<T, E>
+        where
+            T: StackStr + From<String>,
+            E: Error,
+        {
+            if s.len() > self.0 {
+                Err(E::invalid_length(s.len(), &self))
+            } else {
+                Ok(T::from_str_lossless(s))
+            }
+        }
+    }
+
+    struct Truncate(usize);
+
+    impl Transform for Truncate {
+        fn apply<T, E>(self, s: &str) -> Result<T, E>
+        where
+            T: StackStr + From<String>,
+            E: Error,
+        {
+            let mut index = self.0.min(s.len());
+            while !s.is_char_boundary(index) {
+                index -= 1;
+            }
+            Ok(T::from_str_lossless(&s[..index]))
+        }
+    }
+
+    /// Fail if the input is longer than `N` bytes.
+    ///
+    /// # Errors
+    /// Returns an error if the input isn't a string or is too long.
+    pub fn reject<'de, const N: usize, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: StackStr + From<String>,
+    {
+        deserialize_with(deserializer, Reject(N))
+    }
+
+    /// Truncate the input to at most `N` bytes, dropping any character which
+    /// would be split.
+    ///
+    /// # Errors
+    /// Returns an error if the input isn't a string.
+    pub fn truncate<'de, const N: usize, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: StackStr + From<String>,
+    {
+        deserialize_with(deserializer, Truncate(N))
+    }
+}
+
+/// Strip leading and trailing whitespace.
+pub mod trim {
+    use alloc::string::String;
+    use serde::de::{Deserializer, Error};
+
+    use super::{Transform, deserialize_with};
+    use crate::StackStr;
+
+    struct Trim;
+
+    impl Transform for Trim {
+        fn apply<T, E>(self, s: &str) -> Result<T, E>
+        where
+            T: StackStr + From<String>,
+            E: Error,
+        {
+            Ok(T::from_str_lossless(s.trim()))
+        }
+    }
+
+    /// Deserialize a string with leading and trailing whitespace removed.
+    ///
+    /// # Errors
+    /// Returns an error if the input isn't a string.
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: StackStr + From<String>,
+    {
+        deserialize_with(deserializer, Trim)
+    }
+}
+
+/// Convert to lowercase one character at a time, as with
+/// [`char::to_lowercase`].
+pub mod lowercase {
+    use alloc::string::String;
+    use core::fmt::{self, Write};
+    use serde::de::{Deserializer, Error};
+
+    use super::{Transform, build, deserialize_with};
+    use crate::StackStr;
+
+    fn is_lowercase(c: char) -> bool {
+        let mut lower = c.to_lowercase();
+        lower.next() == Some(c) && lower.next().is_none()
+    }
+
+    struct Lowercase<'a>(&'a str);
+
+    impl fmt::Display for Lowercase<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for c in self.0.chars() {
+                for lower in c.to_lowercase() {
+                    f.write_char(lower)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    struct ToLowercase;
+
+    impl Transform for ToLowercase {
+        fn apply<T, E>(self, s: &str) -> Result<T, E>
+        where
+            T: StackStr + From<String>,
+            E: Error,
+        {
+            if s.chars().all(is_lowercase) {
+                Ok(T::from_str_lossless(s))
+            } else {
+                Ok(build(Lowercase(s)))
+            }
+        }
+    }
+
+    /// Deserialize a string converted to lowercase.
+    ///
+    /// # Errors
+    /// Returns an error if the input isn't a string.
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: StackStr + From<String>,
+    {
+        deserialize_with(deserializer, ToLowercase)
+    }
+}
+
+/// Normalize to Unicode normalization form C.
+#[cfg(feature = "nfc")]
+pub mod nfc {
+    use alloc::string::String;
+    use core::fmt::{self, Write};
+    use serde::de::{Deserializer, Error};
+    use unicode_normalization::{IsNormalized, UnicodeNormalization, is_nfc_quick};
+
+    use super::{Transform, build, deserialize_with};
+    use crate::StackStr;
+
+    struct Nfc;
+
+    struct Normalized<'a>(&'a str);
+
+    impl fmt::Display for Normalized<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.nfc().try_for_each(|c| f.write_char(c))
+        }
+    }
+
+    impl Transform for Nfc {
+        fn apply<T, E>(self, s: &str) -> Result<T, E>
+        where
+            T: StackStr + From<String>,
+            E: Error,
+        {
+            match is_nfc_quick(s.chars()) {
+                IsNormalized::Yes => Ok(T::from_str_lossless(s)),
+                _ => Ok(build(Normalized(s))),
+            }
+        }
+    }
+
+    /// Deserialize a string normalized to NFC.
+    ///
+    /// # Errors
+    /// Returns an error if the input isn't a string.
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: StackStr + From<String>,
+    {
+        deserialize_with(deserializer, Nfc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use crate::{SmallString, StackString};
+
+    #[derive(Deserialize, Debug)]
+    struct Limited {
+        #[serde(deserialize_with = "crate::serde_adapters::max_len::reject::<8, _, _>")]
+        rejected: SmallString<8>,
+        #[serde(deserialize_with = "crate::serde_adapters::max_len::truncate::<8, _, _>")]
+        truncated: StackString,
+    }
+
+    #[test]
+    fn test_max_len() {
+        let l: Limited =
+            serde_json::from_str(r#"{"rejected": "12345678", "truncated": "1234567€"}"#).unwrap();
+        assert_eq!(l.rejected, "12345678");
+        assert!(l.rejected.is_inline());
+        assert_eq!(l.truncated, "1234567");
+
+        let l: Limited = serde_json::from_str(r#"{"rejected": "", "truncated": "short"}"#).unwrap();
+        assert_eq!(l.rejected, "");
+        assert_eq!(l.truncated, "short");
+
+        let e = serde_json::from_str::<Limited>(r#"{"rejected": "123456789", "truncated": ""}"#)
+            .unwrap_err();
+        assert!(
+            e.to_string()
+                .starts_with("invalid length 9, expected a string of at most 8 bytes")
+        );
+        assert!(serde_json::from_str::<Limited>(r#"{"rejected": 1, "truncated": ""}"#).is_err());
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Normalized {
+        #[serde(deserialize_with = "crate::serde_adapters::trim::deserialize")]
+        trimmed: SmallString<8>,
+        #[serde(deserialize_with = "crate::serde_adapters::lowercase::deserialize")]
+        lower: StackString,
+    }
+
+    #[test]
+    fn test_trim_lowercase() {
+        let n: Normalized =
+            serde_json::from_str(r#"{"trimmed": " \t padded \n", "lower": "MiXeD ÄÖÜ"}"#).unwrap();
+        assert_eq!(n.trimmed, "padded");
+        assert!(n.trimmed.is_inline());
+        assert_eq!(n.lower, "mixed äöü");
+
+        let long = "A".repeat(300);
+        let input = format!(r#"{{"trimmed": "", "lower": "{long}"}}"#);
+        let n: Normalized = serde_json::from_str(&input).unwrap();
+        assert_eq!(n.lower, long.to_lowercase());
+        assert!(n.lower.is_heap_allocated());
+    }
+
+    #[cfg(feature = "nfc")]
+    #[test]
+    fn test_nfc() {
+        #[derive(Deserialize)]
+        struct Name {
+            #[serde(deserialize_with = "crate::serde_adapters::nfc::deserialize")]
+            name: SmallString<8>,
+        }
+
+        let n: Name = serde_json::from_str(r#"{"name": "José"}"#).unwrap();
+        assert_eq!(n.name, "José");
+        assert_eq!(n.name.len(), 5);
+        assert!(n.name.is_inline());
+
+        let n: Name = serde_json::from_str(r#"{"name": "José"}"#).unwrap();
+        assert_eq!(n.name, "José");
+    }
+}
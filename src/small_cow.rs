@@ -0,0 +1,1256 @@
+use alloc::{
+    borrow::Cow,
+    string::{FromUtf8Error, String},
+    vec::Vec,
+};
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    convert::Infallible,
+    fmt::{self, Write as FmtWrite},
+    hash::{Hash, Hasher},
+    iter::FromIterator,
+    ops::{Add, AddAssign, Deref},
+    str::{self, FromStr, Utf8Error},
+};
+use derive_more::Display;
+use serde::{
+    self, Deserialize, Deserializer, Serialize, Serializer,
+    de::{Error, Unexpected, Visitor},
+};
+#[cfg(feature = "std")]
+use std::{ffi::OsStr, path::Path};
+
+use crate::{
+    small_string::SmallString, stack_cow::StackCow, stack_str::StackStr, stack_string::StackString,
+};
+
+#[cfg(feature = "proptest")]
+use proptest::strategy::{BoxedStrategy, Strategy};
+
+#[cfg(feature = "postgres_types")]
+use bytes::BytesMut;
+#[cfg(feature = "postgres_types")]
+use postgres_types::{FromSql, IsNull, ToSql, Type};
+
+#[cfg(feature = "utoipa_types")]
+use utoipa::{PartialSchema, ToSchema};
+
+#[cfg(feature = "axum_types")]
+use axum::response::IntoResponse;
+
+#[cfg(feature = "axum_types")]
+use axum::body::Body;
+
+#[cfg(feature = "diesel_types")]
+use diesel::{
+    backend::Backend,
+    deserialize::{self, FromSqlRow},
+    expression::AsExpression,
+    serialize,
+    sql_types::Text,
+};
+
+#[cfg(feature = "sqlx_types")]
+use sqlx::{
+    Database, Decode, Encode, MySql, Postgres, Sqlite,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo},
+    sqlite::SqliteArgumentValue,
+};
+
+#[cfg(feature = "rusqlite_types")]
+use rusqlite::types::{FromSqlResult, ToSqlOutput, ValueRef};
+
+#[cfg(feature = "async_graphql")]
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
+
+#[derive(Display, Debug, Clone)]
+#[cfg_attr(feature = "diesel_types", derive(AsExpression, FromSqlRow))]
+#[cfg_attr(feature = "diesel_types", diesel(sql_type = Text))]
+pub enum SmallCow<'a, const CAP: usize> {
+    Borrowed(&'a str),
+    Owned(SmallString<CAP>),
+}
+
+impl<const CAP: usize> PartialEq for SmallCow<'_, CAP> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const CAP: usize> Eq for SmallCow<'_, CAP> {}
+
+impl<const CAP: usize> PartialOrd for SmallCow<'_, CAP> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const CAP: usize> Ord for SmallCow<'_, CAP> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const CAP: usize> Hash for SmallCow<'_, CAP> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl<const CAP: usize> Default for SmallCow<'_, CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const CAP: usize> SmallCow<'a, CAP> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::Owned(SmallString::new())
+    }
+
+    #[must_use]
+    pub fn to_owned(&self) -> SmallCow<'static, CAP> {
+        self.clone().into_owned()
+    }
+
+    #[must_use]
+    pub fn into_owned(self) -> SmallCow<'static, CAP> {
+        match self {
+            Self::Borrowed(b) => SmallCow::Owned(b.into()),
+            Self::Owned(o) => SmallCow::Owned(o),
+        }
+    }
+
+    #[must_use]
+    pub fn is_borrowed(&self) -> bool {
+        match self {
+            Self::Borrowed(_) => true,
+            Self::Owned(_) => false,
+        }
+    }
+
+    #[must_use]
+    pub fn is_owned(&self) -> bool {
+        !self.is_borrowed()
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Borrowed(s) => s,
+            Self::Owned(o) => o.as_str(),
+        }
+    }
+
+    /// Returns a mutable reference to the owned string, converting a
+    /// borrowed value into an owned one first, like `Cow::to_mut`.
+    pub fn to_mut(&mut self) -> &mut SmallString<CAP> {
+        if let Self::Borrowed(s) = *self {
+            *self = Self::Owned(s.into());
+        }
+        match self {
+            Self::Borrowed(_) => unreachable!(),
+            Self::Owned(o) => o,
+        }
+    }
+
+    /// Appends a string slice, a borrowed value is only converted to an
+    /// owned one if `s` is not empty.
+    pub fn push_str(&mut self, s: &str) {
+        if !s.is_empty() {
+            self.to_mut().push_str(s);
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.to_mut().push(c);
+    }
+
+    /// Construct a `SmallCow` from a `&[u8]`, borrowing the input
+    /// # Errors
+    ///
+    /// Will return an Error if the byte slice is not utf8 compliant
+    pub fn from_utf8(v: &'a [u8]) -> Result<Self, Utf8Error> {
+        str::from_utf8(v).map(Self::Borrowed)
+    }
+
+    /// Construct a `SmallCow` from a `Vec<u8>`
+    /// # Errors
+    ///
+    /// Will return an Error if the `Vec<u8>` not utf8 compliant
+    pub fn from_utf8_vec(vec: Vec<u8>) -> Result<Self, FromUtf8Error> {
+        String::from_utf8(vec).map(Into::into)
+    }
+
+    /// Borrows the input if it is valid utf8, otherwise replaces invalid
+    /// sequences with `U+FFFD` in an owned `SmallString`
+    #[must_use]
+    pub fn from_utf8_lossy(v: &'a [u8]) -> Self {
+        match str::from_utf8(v) {
+            Ok(s) => Self::Borrowed(s),
+            Err(_) => Self::Owned(SmallString::from_utf8_lossy(v)),
+        }
+    }
+
+    /// # Panics
+    /// `from_display` panics if a formatting trait implementation returns an
+    /// error. This indicates an incorrect implementation
+    /// since `fmt::Write for String` never returns an error itself.
+    pub fn from_display(buf: impl fmt::Display) -> Self {
+        let mut s = SmallString::new();
+        write!(s, "{buf}").unwrap();
+        s.into()
+    }
+}
+
+impl<const CAP: usize> StackStr for SmallCow<'_, CAP> {
+    fn as_str(&self) -> &str {
+        self.as_str()
+    }
+
+    fn is_inline(&self) -> bool {
+        match self {
+            Self::Borrowed(_) => false,
+            Self::Owned(o) => o.is_inline(),
+        }
+    }
+
+    fn from_str_lossless(s: &str) -> Self {
+        Self::Owned(s.into())
+    }
+
+    fn from_display(buf: impl fmt::Display) -> Self {
+        Self::from_display(buf)
+    }
+
+    fn from_utf8(v: &[u8]) -> Result<Self, Utf8Error> {
+        SmallString::from_utf8(v).map(Self::Owned)
+    }
+
+    fn from_utf8_lossy(v: &[u8]) -> Self {
+        Self::Owned(SmallString::from_utf8_lossy(v))
+    }
+
+    fn split_off(&mut self, index: usize) -> Self {
+        match self {
+            Self::Borrowed(s) => {
+                let (left, right) = s.split_at(index);
+                *s = left;
+                Self::Borrowed(right)
+            }
+            Self::Owned(o) => Self::Owned(o.split_off(index)),
+        }
+    }
+
+    fn into_string(self) -> String {
+        self.into()
+    }
+}
+
+impl<const CAP: usize> Deref for SmallCow<'_, CAP> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Borrowed(b) => b,
+            Self::Owned(o) => o,
+        }
+    }
+}
+
+impl<const CAP: usize> Serialize for SmallCow<'_, CAP> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de: 'a, 'a, const CAP: usize> Deserialize<'de> for SmallCow<'a, CAP> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(SmallCowVisitor)
+    }
+}
+
+impl<const CAP: usize> SmallCow<'static, CAP> {
+    /// Deserialize into an owned `SmallCow`, for use where the input does not
+    /// outlive the result, e.g. `#[serde(deserialize_with =
+    /// "SmallCow::deserialize_owned")]`
+    /// # Errors
+    ///
+    /// Will return an Error if the deserializer does not produce a string
+    pub fn deserialize_owned<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        SmallString::<CAP>::deserialize(deserializer).map(Into::into)
+    }
+}
+
+struct SmallCowVisitor<const CAP: usize>;
+
+impl<'de, const CAP: usize> Visitor<'de> for SmallCowVisitor<CAP> {
+    type Value = SmallCow<'de, CAP>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(SmallCow::Borrowed(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(SmallCow::Owned(v.into()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(SmallCow::Owned(v.into()))
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        match str::from_utf8(v) {
+            Ok(s) => Ok(SmallCow::Borrowed(s)),
+            Err(_) => Err(Error::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        match str::from_utf8(v) {
+            Ok(s) => Ok(SmallCow::Owned(s.into())),
+            Err(_) => Err(Error::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        match String::from_utf8(v) {
+            Ok(s) => Ok(SmallCow::Owned(s.into())),
+            Err(e) => Err(Error::invalid_value(
+                Unexpected::Bytes(&e.into_bytes()),
+                &self,
+            )),
+        }
+    }
+}
+
+impl<const CAP: usize> From<SmallString<CAP>> for SmallCow<'_, CAP> {
+    fn from(item: SmallString<CAP>) -> Self {
+        Self::Owned(item)
+    }
+}
+
+impl<'a, const CAP: usize> From<SmallCow<'a, CAP>> for SmallString<CAP> {
+    fn from(item: SmallCow<'a, CAP>) -> Self {
+        match item {
+            SmallCow::Borrowed(s) => s.into(),
+            SmallCow::Owned(s) => s,
+        }
+    }
+}
+
+impl<const CAP: usize> From<StackString> for SmallCow<'_, CAP> {
+    fn from(item: StackString) -> Self {
+        Self::Owned(item.into())
+    }
+}
+
+impl<const CAP: usize> From<SmallCow<'_, CAP>> for StackString {
+    fn from(item: SmallCow<'_, CAP>) -> Self {
+        match item {
+            SmallCow::Borrowed(s) => s.into(),
+            SmallCow::Owned(s) => s.into(),
+        }
+    }
+}
+
+impl<'a, const CAP: usize> From<StackCow<'a>> for SmallCow<'a, CAP> {
+    fn from(item: StackCow<'a>) -> Self {
+        match item {
+            StackCow::Borrowed(s) => Self::Borrowed(s),
+            StackCow::Owned(s) => Self::Owned(s.into()),
+        }
+    }
+}
+
+impl<'a, const CAP: usize> From<SmallCow<'a, CAP>> for StackCow<'a> {
+    fn from(item: SmallCow<'a, CAP>) -> Self {
+        match item {
+            SmallCow::Borrowed(s) => Self::Borrowed(s),
+            SmallCow::Owned(s) => Self::Owned(s.into()),
+        }
+    }
+}
+
+impl<'a, const CAP: usize> From<Cow<'a, str>> for SmallCow<'a, CAP> {
+    fn from(item: Cow<'a, str>) -> Self {
+        match item {
+            Cow::Borrowed(s) => Self::Borrowed(s),
+            Cow::Owned(s) => Self::Owned(s.into()),
+        }
+    }
+}
+
+impl<const CAP: usize> From<SmallCow<'_, CAP>> for String {
+    fn from(item: SmallCow<CAP>) -> Self {
+        match item {
+            SmallCow::Borrowed(s) => s.into(),
+            SmallCow::Owned(s) => s.into(),
+        }
+    }
+}
+
+impl<const CAP: usize> From<&SmallCow<'_, CAP>> for String {
+    fn from(item: &SmallCow<CAP>) -> Self {
+        item.as_str().into()
+    }
+}
+
+impl<const CAP: usize> From<String> for SmallCow<'_, CAP> {
+    fn from(item: String) -> Self {
+        Self::Owned(item.into())
+    }
+}
+
+impl<'a, const CAP: usize> From<&'a String> for SmallCow<'a, CAP> {
+    fn from(item: &'a String) -> Self {
+        Self::Borrowed(item.as_str())
+    }
+}
+
+impl<'a, const CAP: usize> From<&'a str> for SmallCow<'a, CAP> {
+    fn from(item: &'a str) -> Self {
+        SmallCow::Borrowed(item)
+    }
+}
+
+impl<'a, const CAP: usize> From<&'a SmallCow<'a, CAP>> for &'a str {
+    fn from(item: &'a SmallCow<CAP>) -> &'a str {
+        item.as_str()
+    }
+}
+
+impl<const CAP: usize> Borrow<str> for SmallCow<'_, CAP> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const CAP: usize> AsRef<str> for SmallCow<'_, CAP> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const CAP: usize> AsRef<[u8]> for SmallCow<'_, CAP> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_str().as_bytes()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const CAP: usize> AsRef<OsStr> for SmallCow<'_, CAP> {
+    fn as_ref(&self) -> &OsStr {
+        self.as_str().as_ref()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const CAP: usize> AsRef<Path> for SmallCow<'_, CAP> {
+    fn as_ref(&self) -> &Path {
+        Path::new(self)
+    }
+}
+
+impl<const CAP: usize> FromStr for SmallCow<'_, CAP> {
+    type Err = Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::Owned(s.into()))
+    }
+}
+
+impl<'a, const CAP: usize> PartialEq<Cow<'a, str>> for SmallCow<'_, CAP> {
+    #[inline]
+    fn eq(&self, other: &Cow<'a, str>) -> bool {
+        PartialEq::eq(&self[..], &other[..])
+    }
+}
+
+impl<'a, const CAP: usize> PartialOrd<Cow<'a, str>> for SmallCow<'_, CAP> {
+    fn partial_cmp(&self, other: &Cow<'a, str>) -> Option<core::cmp::Ordering> {
+        PartialOrd::partial_cmp(&self[..], &other[..])
+    }
+}
+
+impl<const CAP: usize> PartialEq<String> for SmallCow<'_, CAP> {
+    #[inline]
+    fn eq(&self, other: &String) -> bool {
+        PartialEq::eq(&self[..], &other[..])
+    }
+}
+
+impl<const CAP: usize> PartialOrd<String> for SmallCow<'_, CAP> {
+    fn partial_cmp(&self, other: &String) -> Option<core::cmp::Ordering> {
+        PartialOrd::partial_cmp(&self[..], &other[..])
+    }
+}
+
+impl<const CAP: usize> PartialEq<str> for SmallCow<'_, CAP> {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        let s: &str = self.as_ref();
+        PartialEq::eq(s, other)
+    }
+}
+
+impl<const CAP: usize> PartialEq<&str> for SmallCow<'_, CAP> {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        PartialEq::eq(&self[..], &other[..])
+    }
+}
+
+impl<'a, const CAP: usize> PartialEq<SmallCow<'a, CAP>> for &str {
+    fn eq(&self, other: &SmallCow<'a, CAP>) -> bool {
+        self.eq(&other.as_str())
+    }
+}
+
+impl<const CAP: usize> FromIterator<char> for SmallCow<'_, CAP> {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        Self::Owned(SmallString::from_iter(iter))
+    }
+}
+
+impl<const CAP: usize> fmt::Write for SmallCow<'_, CAP> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl<const CAP: usize> Extend<char> for SmallCow<'_, CAP> {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter().peekable();
+        if iter.peek().is_some() {
+            let s = self.to_mut();
+            for c in iter {
+                s.push(c);
+            }
+        }
+    }
+}
+
+impl<'b, const CAP: usize> Extend<&'b str> for SmallCow<'_, CAP> {
+    fn extend<I: IntoIterator<Item = &'b str>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter().filter(|s| !s.is_empty()).peekable();
+        if iter.peek().is_some() {
+            let s = self.to_mut();
+            for item in iter {
+                s.push_str(item);
+            }
+        }
+    }
+}
+
+impl<const CAP: usize> Extend<String> for SmallCow<'_, CAP> {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        for s in iter {
+            self.push_str(&s);
+        }
+    }
+}
+
+impl<'b, const CAP: usize> FromIterator<&'b str> for SmallCow<'_, CAP> {
+    fn from_iter<I: IntoIterator<Item = &'b str>>(iter: I) -> Self {
+        Self::Owned(SmallString::from_iter(iter))
+    }
+}
+
+impl<const CAP: usize> FromIterator<String> for SmallCow<'_, CAP> {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        Self::Owned(SmallString::from_iter(iter))
+    }
+}
+
+impl<'a, const CAP: usize> FromIterator<SmallCow<'a, CAP>> for SmallCow<'a, CAP> {
+    fn from_iter<I: IntoIterator<Item = SmallCow<'a, CAP>>>(iter: I) -> Self {
+        let mut iter = iter.into_iter();
+        let mut s = iter.next().unwrap_or_default();
+        for item in iter {
+            s.push_str(&item);
+        }
+        s
+    }
+}
+
+impl<const CAP: usize> Add<&str> for SmallCow<'_, CAP> {
+    type Output = Self;
+
+    fn add(mut self, rhs: &str) -> Self {
+        self.push_str(rhs);
+        self
+    }
+}
+
+impl<const CAP: usize> AddAssign<&str> for SmallCow<'_, CAP> {
+    fn add_assign(&mut self, rhs: &str) {
+        self.push_str(rhs);
+    }
+}
+
+impl<const CAP: usize> PartialOrd<str> for SmallCow<'_, CAP> {
+    fn partial_cmp(&self, other: &str) -> Option<core::cmp::Ordering> {
+        self.as_str().partial_cmp(other)
+    }
+}
+
+impl<const CAP: usize> PartialOrd<&str> for SmallCow<'_, CAP> {
+    fn partial_cmp(&self, other: &&str) -> Option<core::cmp::Ordering> {
+        self.as_str().partial_cmp(*other)
+    }
+}
+
+impl<'a, const CAP: usize> PartialOrd<SmallCow<'a, CAP>> for &str {
+    fn partial_cmp(&self, other: &SmallCow<'a, CAP>) -> Option<core::cmp::Ordering> {
+        self.partial_cmp(&other.as_str())
+    }
+}
+
+#[cfg(feature = "postgres_types")]
+impl<'a, const CAP: usize> FromSql<'a> for SmallCow<'a, CAP> {
+    fn from_sql(
+        ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let s = <&'a str as FromSql>::from_sql(ty, raw)?;
+        Ok(s.into())
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <&'a str as FromSql>::accepts(ty)
+    }
+}
+
+#[cfg(feature = "postgres_types")]
+impl<'a, const CAP: usize> ToSql for SmallCow<'a, CAP> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn std::error::Error + Sync + Send>>
+    where
+        Self: Sized,
+    {
+        ToSql::to_sql(&self.as_str(), ty, out)
+    }
+
+    fn accepts(ty: &Type) -> bool
+    where
+        Self: Sized,
+    {
+        <String as ToSql>::accepts(ty)
+    }
+
+    fn to_sql_checked(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        self.as_str().to_sql_checked(ty, out)
+    }
+}
+
+#[cfg(feature = "diesel_types")]
+impl<DB, const CAP: usize> serialize::ToSql<Text, DB> for SmallCow<'_, CAP>
+where
+    DB: Backend,
+    str: serialize::ToSql<Text, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut serialize::Output<'b, '_, DB>) -> serialize::Result {
+        <str as serialize::ToSql<Text, DB>>::to_sql(self.as_str(), out)
+    }
+}
+
+#[cfg(feature = "diesel_types")]
+impl<DB, const CAP: usize> deserialize::FromSql<Text, DB> for SmallCow<'_, CAP>
+where
+    DB: Backend,
+    *const str: deserialize::FromSql<Text, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        let ptr = <*const str as deserialize::FromSql<Text, DB>>::from_sql(bytes)?;
+        // The pointer is only valid for the lifetime of `bytes`, so the
+        // contents must be copied into an owned value before returning
+        let s = unsafe { &*ptr };
+        Ok(Self::Owned(s.into()))
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<DB, const CAP: usize> sqlx::Type<DB> for SmallCow<'_, CAP>
+where
+    DB: Database,
+    str: sqlx::Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <str as sqlx::Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <str as sqlx::Type<DB>>::compatible(ty)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<'r, DB, const CAP: usize> Decode<'r, DB> for SmallCow<'r, CAP>
+where
+    DB: Database,
+    &'r str: Decode<'r, DB>,
+{
+    fn decode(value: DB::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        <&'r str as Decode<'r, DB>>::decode(value).map(Self::Borrowed)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<const CAP: usize> Encode<'_, Postgres> for SmallCow<'_, CAP> {
+    fn encode_by_ref(
+        &self,
+        buf: &mut PgArgumentBuffer,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <&str as Encode<Postgres>>::encode(self.as_str(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<const CAP: usize> Encode<'_, MySql> for SmallCow<'_, CAP> {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <&str as Encode<MySql>>::encode(self.as_str(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<'q, const CAP: usize> Encode<'q, Sqlite> for SmallCow<'_, CAP> {
+    fn encode(
+        self,
+        buf: &mut Vec<SqliteArgumentValue<'q>>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <String as Encode<Sqlite>>::encode(self.into(), buf)
+    }
+
+    fn encode_by_ref(
+        &self,
+        buf: &mut Vec<SqliteArgumentValue<'q>>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <String as Encode<Sqlite>>::encode(self.into(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<const CAP: usize> PgHasArrayType for SmallCow<'_, CAP> {
+    fn array_type_info() -> PgTypeInfo {
+        <&str as PgHasArrayType>::array_type_info()
+    }
+
+    fn array_compatible(ty: &PgTypeInfo) -> bool {
+        <&str as PgHasArrayType>::array_compatible(ty)
+    }
+}
+
+#[cfg(feature = "rusqlite_types")]
+impl<const CAP: usize> rusqlite::types::ToSql for SmallCow<'_, CAP> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(ValueRef::Text(self.as_bytes())))
+    }
+}
+
+#[cfg(feature = "rusqlite_types")]
+impl<const CAP: usize> rusqlite::types::FromSql for SmallCow<'_, CAP> {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        // The value is only borrowed for the duration of this call, so the
+        // result must be owned
+        value.as_str().map(|s| Self::Owned(s.into()))
+    }
+}
+
+#[cfg(feature = "utoipa_types")]
+impl<'a, const CAP: usize> PartialSchema for SmallCow<'a, CAP> {
+    fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
+        str::schema()
+    }
+}
+
+#[cfg(feature = "utoipa_types")]
+impl<'a, const CAP: usize> ToSchema for SmallCow<'a, CAP> {
+    fn name() -> Cow<'static, str> {
+        str::name()
+    }
+}
+
+#[cfg(feature = "axum_types")]
+impl<'a, const CAP: usize> IntoResponse for SmallCow<'a, CAP> {
+    fn into_response(self) -> axum::response::Response {
+        let s: String = self.into();
+        s.into_response()
+    }
+}
+
+#[cfg(feature = "axum_types")]
+impl<'a, const CAP: usize> From<SmallCow<'a, CAP>> for Body {
+    fn from(value: SmallCow<'a, CAP>) -> Self {
+        let s: String = value.into();
+        s.into()
+    }
+}
+
+/// Allow SmallCow to be used as graphql scalar value
+#[cfg(feature = "async_graphql")]
+#[Scalar]
+impl<'a, const CAP: usize> ScalarType for SmallCow<'a, CAP> {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        if let Value::String(s) = value {
+            let s: SmallCow<'a, CAP> = s.into();
+            Ok(s)
+        } else {
+            Err(InputValueError::expected_type(value))
+        }
+    }
+
+    fn is_valid(value: &Value) -> bool {
+        matches!(value, Value::String(_))
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+/// Generates both borrowed and owned values.
+#[cfg(feature = "arbitrary")]
+impl<'a, const CAP: usize> arbitrary::Arbitrary<'a> for SmallCow<'a, CAP> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        if u.arbitrary()? {
+            u.arbitrary().map(Self::Borrowed)
+        } else {
+            u.arbitrary().map(Self::Owned)
+        }
+    }
+}
+
+/// Only generates owned values.
+#[cfg(feature = "proptest")]
+impl<const CAP: usize> proptest::arbitrary::Arbitrary for SmallCow<'static, CAP> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        proptest::arbitrary::any::<SmallString<CAP>>()
+            .prop_map(Self::Owned)
+            .boxed()
+    }
+}
+
+/// Only generates owned values.
+#[cfg(feature = "quickcheck")]
+impl<const CAP: usize> quickcheck::Arbitrary for SmallCow<'static, CAP> {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        Self::Owned(quickcheck::Arbitrary::arbitrary(g))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let s = SmallString::<CAP>::from(self.as_str());
+        Box::new(quickcheck::Arbitrary::shrink(&s).map(Self::Owned))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, rng as thread_rng};
+    use serde::Deserialize;
+
+    use crate::{SmallCow, SmallString, StackStr};
+
+    #[test]
+    fn test_default() {
+        assert_eq!(SmallCow::<5>::new(), SmallCow::<5>::default());
+    }
+
+    #[test]
+    fn test_from_utf8() {
+        let mut rng = thread_rng();
+        let v: Vec<_> = (0..20).map(|_| rng.random::<u8>() & 0x7f).collect();
+        let s0 = String::from_utf8(v.clone()).unwrap();
+        let s1 = SmallCow::<5>::from_utf8(&v).unwrap();
+        assert_eq!(s0.as_str(), s1.as_str());
+        assert!(s1.is_borrowed());
+        let s1 = SmallCow::<5>::from_utf8_vec(v).unwrap();
+        assert_eq!(s0.as_str(), s1.as_str());
+        assert!(s1.is_owned());
+
+        let v: Vec<_> = (0..20).map(|_| rng.random::<u8>()).collect();
+        let s0 = std::str::from_utf8(&v);
+        let s1 = SmallCow::<5>::from_utf8(&v);
+
+        match s0 {
+            Ok(s) => assert_eq!(s, s1.unwrap().as_str()),
+            Err(e) => assert_eq!(e, s1.unwrap_err()),
+        }
+    }
+
+    #[test]
+    fn test_into_owned() {
+        let s = String::from("Hello");
+        let c: SmallCow<'_, 5> = s.as_str().into();
+        assert!(c.is_borrowed());
+        let c = c.into_owned();
+        drop(s);
+        assert!(c.is_owned());
+        assert_eq!(c.as_str(), "Hello");
+        let o: SmallString<5> = c.into();
+        assert!(o.is_inline());
+    }
+
+    #[test]
+    fn test_string_from_small_cow() {
+        let s0 = SmallCow::<5>::from("Hello there");
+        let s1: String = s0.clone().into();
+        assert_eq!(s0.as_str(), s1.as_str());
+    }
+
+    #[test]
+    fn test_small_cow_from_string() {
+        let s0 = String::from("Hello there");
+        let s1: SmallCow<20> = s0.clone().into();
+        assert_eq!(s0.as_str(), s1.as_str());
+        let s1: SmallCow<20> = (&s0).into();
+        assert_eq!(s0.as_str(), s1.as_str());
+    }
+
+    #[test]
+    fn test_as_ref() {
+        let s = SmallCow::<5>::from("Hello");
+        let st: &str = s.as_ref();
+        assert_eq!(st, s.as_str());
+        let bt: &[u8] = s.as_ref();
+        assert_eq!(bt, s.as_bytes());
+        #[cfg(feature = "std")]
+        {
+            use std::path::Path;
+
+            let pt: &Path = s.as_ref();
+            assert_eq!(pt, Path::new("Hello"));
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cmp_owned)]
+    fn test_partial_eq_string() {
+        assert_eq!(SmallCow::<5>::from("Hello"), String::from("Hello"));
+        assert_eq!(SmallCow::<5>::from("Hello"), "Hello");
+        assert_eq!(&SmallCow::<5>::from("Hello"), "Hello");
+        assert!(SmallCow::<5>::from("alpha") < "beta");
+        assert!("beta" > SmallCow::<5>::from("alpha"));
+    }
+
+    #[test]
+    fn test_from_iterator_char() {
+        let mut rng = thread_rng();
+        let v: Vec<char> = (0..20).map(|_| rng.random::<char>()).collect();
+        let s0: SmallCow<20> = v.iter().copied().collect();
+        let s1: String = v.iter().copied().collect();
+        assert_eq!(s0, s1);
+    }
+
+    #[test]
+    fn test_borrowed_owned_eq_hash() {
+        use std::collections::HashSet;
+
+        let borrowed = SmallCow::<8>::Borrowed("alpha");
+        let owned = SmallCow::<8>::Owned("alpha".into());
+        assert_eq!(borrowed, owned);
+        assert!(SmallCow::<8>::Borrowed("beta") > owned);
+        assert!(SmallCow::<8>::Owned("beta".into()) > borrowed);
+
+        let set: HashSet<SmallCow<8>> = [borrowed].into_iter().collect();
+        assert!(set.contains(&owned));
+        assert!(set.contains("alpha"));
+        let set: HashSet<SmallCow<8>> = [owned].into_iter().collect();
+        assert!(set.contains(&SmallCow::Borrowed("alpha")));
+        assert!(set.contains("alpha"));
+    }
+
+    #[cfg(feature = "postgres_types")]
+    use bytes::BytesMut;
+    #[cfg(feature = "postgres_types")]
+    use postgres_types::{FromSql, IsNull, ToSql, Type};
+
+    #[cfg(feature = "postgres_types")]
+    #[test]
+    fn test_from_sql() {
+        let raw = b"Hello There";
+        let t = Type::TEXT;
+        let s = SmallCow::<5>::from_sql(&t, raw).unwrap();
+        assert_eq!(s, SmallCow::from("Hello There"));
+        assert!(s.is_borrowed());
+
+        assert!(<SmallCow<5> as FromSql>::accepts(&t));
+    }
+
+    #[cfg(feature = "postgres_types")]
+    #[test]
+    fn test_to_sql() {
+        let s = SmallCow::<5>::from("Hello There");
+        let t = Type::TEXT;
+        assert!(<SmallCow<5> as ToSql>::accepts(&t));
+        let mut buf = BytesMut::new();
+        match s.to_sql(&t, &mut buf).unwrap() {
+            IsNull::Yes => panic!(),
+            IsNull::No => {}
+        }
+        assert_eq!(buf.as_ref(), b"Hello There");
+    }
+
+    #[cfg(feature = "rusqlite_types")]
+    #[test]
+    fn test_rusqlite() {
+        use rusqlite::Connection;
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE test_strings (s TEXT NOT NULL)", [])
+            .unwrap();
+
+        let s = SmallCow::<5>::from("Hello There");
+        conn.execute("INSERT INTO test_strings (s) VALUES (?1)", [&s])
+            .unwrap();
+
+        let result: SmallCow<5> = conn
+            .query_row("SELECT s FROM test_strings", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(result.as_str(), s.as_str());
+        assert!(result.is_owned());
+    }
+
+    #[test]
+    fn test_to_mut() {
+        let input = String::from("hello");
+        let mut s: SmallCow<5> = input.as_str().into();
+        s.push_str("");
+        assert!(s.is_borrowed());
+        s.to_mut().make_ascii_uppercase();
+        assert!(s.is_owned());
+        assert_eq!(s, "HELLO");
+        s.push(' ');
+        s.push_str("there");
+        assert_eq!(s, "HELLO there");
+
+        let mut s: SmallCow<5> = SmallString::from("a").into();
+        s.to_mut().push('b');
+        assert_eq!(s, "ab");
+    }
+
+    #[test]
+    fn test_add_extend_collect() {
+        let input = String::from("hello");
+        let mut s: SmallCow<5> = input.as_str().into();
+        s += "";
+        s.extend(Vec::<String>::new());
+        assert!(s.is_borrowed());
+        let mut s = s + " there";
+        s.extend([String::from("!")]);
+        assert!(s.is_owned());
+        assert_eq!(s, "hello there!");
+
+        let s: SmallCow<5> = ["a", "b"].into_iter().collect();
+        assert_eq!(s, "ab");
+        let s: SmallCow<5> = [String::from("a"), String::from("b")].into_iter().collect();
+        assert_eq!(s, "ab");
+        let s: SmallCow<5> = [
+            SmallCow::<5>::from(input.as_str()),
+            SmallCow::<5>::default(),
+        ]
+        .into_iter()
+        .collect();
+        assert!(s.is_borrowed());
+        let s: SmallCow<5> = [SmallCow::<5>::from(input.as_str()), " world".into()]
+            .into_iter()
+            .collect();
+        assert!(s.is_owned());
+        assert_eq!(s, "hello world");
+    }
+
+    #[test]
+    fn test_write_extend() {
+        use std::fmt::Write;
+
+        let input = String::from("hello");
+        let mut s: SmallCow<5> = input.as_str().into();
+        s.extend(std::iter::empty::<char>());
+        s.extend(["", ""]);
+        write!(s, "").unwrap();
+        assert!(s.is_borrowed());
+        s.extend([' ', 'a']);
+        assert!(s.is_owned());
+        s.extend(["", "nd", " more"]);
+        write!(s, " {}", 1).unwrap();
+        assert_eq!(s, "hello and more 1");
+    }
+
+    #[test]
+    fn test_from_display() {
+        use std::fmt::Display;
+
+        struct Test {}
+
+        impl Display for Test {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("THIS IS A TEST")
+            }
+        }
+
+        let t = Test {};
+        let s = SmallCow::<20>::from_display(t);
+        assert_eq!(s, SmallCow::from(SmallString::from("THIS IS A TEST")));
+    }
+
+    #[test]
+    fn test_from_utf8_lossy() {
+        let s = SmallCow::<20>::from_utf8_lossy(b"this is a test");
+        assert!(s.is_borrowed());
+        assert_eq!(s, "this is a test");
+
+        let mut v = Vec::new();
+        v.extend_from_slice("this is a test".as_bytes());
+        v.push(0xff);
+        v.extend_from_slice("yes".as_bytes());
+        let s = SmallCow::<20>::from_utf8_lossy(&v);
+        assert_eq!(s.len(), 20);
+        assert!(s.is_owned());
+        let s: SmallString<20> = s.into();
+        assert_eq!(s.len(), 20);
+        assert!(s.is_inline());
+
+        // two replacement characters need 6 bytes
+        let s = SmallCow::<4>::from_utf8_lossy(&[0xff, 0xff]);
+        assert_eq!(s, "\u{FFFD}\u{FFFD}");
+        let s = <SmallCow<4> as StackStr>::from_utf8_lossy(b"ab\xff");
+        assert_eq!(s, "ab\u{FFFD}");
+    }
+
+    #[test]
+    fn test_serde() {
+        let s = SmallCow::<5>::from("HELLO");
+        let t = "HELLO";
+        let s = serde_json::to_vec(&s).unwrap();
+        let t = serde_json::to_vec(t).unwrap();
+        assert_eq!(s, t);
+
+        #[derive(Deserialize)]
+        struct A<'a> {
+            #[serde(borrow)]
+            a: SmallCow<'a, 5>,
+        }
+
+        let a: A = serde_json::from_str(r#"{"a": "b"}"#).unwrap();
+        assert_eq!(a.a.as_str(), "b");
+        assert!(a.a.is_borrowed());
+
+        let a: A = serde_json::from_str(r#"{"a": "b\nc"}"#).unwrap();
+        assert_eq!(a.a.as_str(), "b\nc");
+        assert!(a.a.is_owned());
+
+        #[derive(Deserialize)]
+        struct C {
+            #[serde(deserialize_with = "SmallCow::deserialize_owned")]
+            a: SmallCow<'static, 5>,
+        }
+
+        let c: C = serde_json::from_reader(r#"{"a": "b"}"#.as_bytes()).unwrap();
+        assert_eq!(c.a.as_str(), "b");
+        assert!(c.a.is_owned());
+    }
+
+    #[cfg(feature = "async_graphql")]
+    #[test]
+    fn test_smallcow_async_graphql() {
+        use async_graphql::{
+            Context, EmptyMutation, EmptySubscription, Object, Schema,
+            dataloader::{DataLoader, Loader},
+        };
+        use std::{collections::HashMap, convert::Infallible};
+
+        struct SmallCowLoader;
+
+        impl SmallCowLoader {
+            fn new() -> Self {
+                Self
+            }
+        }
+
+        impl<const CAP: usize> Loader<SmallString<CAP>> for SmallCowLoader {
+            type Value = SmallCow<'static, CAP>;
+            type Error = Infallible;
+
+            async fn load(
+                &self,
+                _: &[SmallString<CAP>],
+            ) -> Result<HashMap<SmallString<CAP>, Self::Value>, Self::Error> {
+                let mut m = HashMap::new();
+                m.insert("HELLO".into(), "WORLD".into());
+                Ok(m)
+            }
+        }
+
+        struct QueryRoot<const CAP: usize>;
+
+        #[Object]
+        impl<const CAP: usize> QueryRoot<CAP> {
+            async fn hello<'a>(
+                &self,
+                ctx: &Context<'a>,
+            ) -> Result<Option<SmallCow<'static, CAP>>, Infallible> {
+                let hello = ctx
+                    .data::<DataLoader<SmallCowLoader>>()
+                    .unwrap()
+                    .load_one("hello".into())
+                    .await
+                    .unwrap();
+                Ok(hello)
+            }
+        }
+
+        let expected_sdl = include_str!("../tests/data/sdl_file_smallcow.txt");
+
+        let schema = Schema::build(QueryRoot::<5>, EmptyMutation, EmptySubscription)
+            .data(DataLoader::new(SmallCowLoader::new(), tokio::task::spawn))
+            .finish();
+        let sdl = schema.sdl();
+
+        std::fs::write("sdl_file_smallcow.txt", &sdl).unwrap();
+        assert_eq!(&sdl, expected_sdl);
+    }
+}
@@ -1,23 +1,29 @@
+use alloc::{
+    borrow::Cow,
+    string::{FromUtf8Error, String, ToString},
+    vec::Vec,
+};
 use arrayvec::ArrayString;
 use core::marker::PhantomData;
-use serde::{
-    Deserialize, Deserializer, Serialize, Serializer,
-    de::{Error, Unexpected, Visitor},
-};
-use std::{
-    borrow::{Borrow, BorrowMut, Cow},
+use core::{
+    borrow::{Borrow, BorrowMut},
+    cmp::Ordering,
     convert::Infallible,
-    ffi::OsStr,
     fmt,
     fmt::Write as FmtWrite,
-    iter::FromIterator,
+    hash::{Hash, Hasher},
+    iter::{FromIterator, FusedIterator},
     mem,
-    ops::{Deref, DerefMut},
-    path::Path,
+    ops::{Add, AddAssign, Bound, Deref, DerefMut, RangeBounds},
     str,
     str::{FromStr, Utf8Error},
-    string::FromUtf8Error,
 };
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{Error, Unexpected, Visitor},
+};
+#[cfg(feature = "std")]
+use std::{ffi::OsStr, path::Path};
 
 #[cfg(feature = "postgres_types")]
 use bytes::BytesMut;
@@ -33,20 +39,129 @@ use axum::response::IntoResponse;
 #[cfg(feature = "axum_types")]
 use axum::body::Body;
 
+#[cfg(feature = "diesel_types")]
+use diesel::{
+    backend::Backend,
+    deserialize::{self, FromSqlRow},
+    expression::AsExpression,
+    serialize,
+    sql_types::Text,
+};
+
+#[cfg(feature = "sqlx_types")]
+use sqlx::{
+    Database, Decode, Encode, MySql, Postgres, Sqlite,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo},
+    sqlite::SqliteArgumentValue,
+};
+
+#[cfg(feature = "rusqlite_types")]
+use rusqlite::types::{FromSqlResult, ToSqlOutput, ValueRef};
+
 #[cfg(feature = "async_graphql")]
 use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
 
-use crate::StackString;
+use crate::{
+    StackString,
+    boxed_string::BoxedString,
+    inline_string::InlineString,
+    join::{joined_len, push_joined},
+    stack_str::StackStr,
+};
+
+#[cfg(any(feature = "bincode", feature = "borsh"))]
+use crate::binary;
+
+#[cfg(feature = "bincode")]
+use bincode::{
+    de::{BorrowDecoder, Decoder},
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+};
+
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[cfg(feature = "rkyv")]
+use rkyv::{
+    Archive, DeserializeUnsized, Place, SerializeUnsized,
+    rancor::{Fallible, Source},
+    string::{ArchivedString, StringResolver},
+};
+
+#[cfg(any(feature = "arbitrary", feature = "proptest", feature = "quickcheck"))]
+use crate::generators;
+
+#[cfg(feature = "proptest")]
+use proptest::strategy::{BoxedStrategy, Strategy};
+
+/// A string stored inline when it fits in `CAP` bytes and on the heap
+/// otherwise.
+///
+/// On 64 bit targets a `SmallString<CAP>` is `max(CAP + 1, 24)` bytes rounded
+/// up to a multiple of 8, the inline length and the tag share the last byte.
+#[derive(Clone)]
+#[cfg_attr(feature = "diesel_types", derive(AsExpression, FromSqlRow))]
+#[cfg_attr(feature = "diesel_types", diesel(sql_type = Text))]
+pub struct SmallString<const CAP: usize>(Repr<CAP>);
+
+#[derive(Clone)]
+enum Repr<const CAP: usize> {
+    Inline(InlineString<CAP>),
+    Boxed(BoxedString),
+    /// A string literal longer than `CAP`, see [`SmallString::from_static`],
+    /// it is moved to the heap when modified and inline once shortened to
+    /// `CAP` bytes or fewer.
+    Static(&'static str),
+}
+
+impl<const CAP: usize> Repr<CAP> {
+    #[inline]
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Inline(s) => s.as_str(),
+            Self::Boxed(s) => s.as_str(),
+            Self::Static(s) => s,
+        }
+    }
+}
+
+impl<const CAP: usize> fmt::Debug for SmallString<CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const CAP: usize> PartialEq for SmallString<CAP> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const CAP: usize> Eq for SmallString<CAP> {}
+
+impl<const CAP: usize> PartialOrd for SmallString<CAP> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const CAP: usize> Ord for SmallString<CAP> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub enum SmallString<const CAP: usize> {
-    Inline(ArrayString<CAP>),
-    Boxed(String),
+impl<const CAP: usize> Hash for SmallString<CAP> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
 }
 
 impl<const CAP: usize> Default for SmallString<CAP> {
     fn default() -> Self {
-        Self::Inline(ArrayString::new())
+        Self::new()
     }
 }
 
@@ -54,38 +169,90 @@ impl<const CAP: usize> SmallString<CAP> {
     #[inline]
     #[must_use]
     pub fn new() -> Self {
-        Self::Inline(ArrayString::new())
+        Self(Repr::Inline(InlineString::new()))
+    }
+
+    /// Construct a `SmallString` from a string literal in a const context,
+    /// the literal is copied inline if it fits in `CAP` bytes, otherwise the
+    /// static reference is kept.
+    #[must_use]
+    pub const fn from_static(s: &'static str) -> Self {
+        if s.len() <= CAP {
+            Self(Repr::Inline(InlineString::from_str_const(s)))
+        } else {
+            Self(Repr::Static(s))
+        }
+    }
+
+    /// Create an empty `SmallString`, on the heap if `capacity` exceeds
+    /// `CAP`.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        if capacity > CAP {
+            Self::boxed(String::with_capacity(capacity))
+        } else {
+            Self::new()
+        }
+    }
+
+    /// Concatenate the parts, choosing inline or heap storage once up front.
+    #[must_use]
+    pub fn concat<T: AsRef<str>>(parts: &[T]) -> Self {
+        Self::join(parts, "")
+    }
+
+    /// Join the parts with `sep`, choosing inline or heap storage once up
+    /// front.
+    #[must_use]
+    pub fn join<I, T>(parts: I, sep: &str) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Clone,
+        T: AsRef<str>,
+    {
+        let parts = parts.into_iter();
+        let mut s = Self::with_capacity(joined_len(parts.clone(), sep));
+        push_joined(&mut s, parts, sep);
+        s
     }
 
+    /// Exactly one of `is_inline`, `is_boxed` and `is_static` is true, so a
+    /// string which is not inline is not necessarily on the heap.
     #[inline]
     #[must_use]
     pub fn is_inline(&self) -> bool {
-        match self {
-            Self::Inline(_) => true,
-            Self::Boxed(_) => false,
-        }
+        matches!(self.0, Repr::Inline(_))
     }
 
     #[inline]
     #[must_use]
     pub fn is_boxed(&self) -> bool {
-        !self.is_inline()
+        matches!(self.0, Repr::Boxed(_))
+    }
+
+    /// Returns true for a literal longer than `CAP` kept by
+    /// [`SmallString::from_static`].
+    #[inline]
+    #[must_use]
+    pub fn is_static(&self) -> bool {
+        matches!(self.0, Repr::Static(_))
     }
 
     #[inline]
     #[must_use]
     pub fn as_str(&self) -> &str {
-        match self {
-            Self::Inline(s) => s.as_str(),
-            Self::Boxed(s) => s.as_str(),
-        }
+        self.0.as_str()
     }
 
     #[inline]
     pub fn as_mut_str(&mut self) -> &mut str {
-        match self {
-            Self::Inline(s) => s.as_mut_str(),
-            Self::Boxed(s) => s.as_mut_str(),
+        if self.is_static() {
+            self.make_boxed(0);
+        }
+        match &mut self.0 {
+            Repr::Inline(s) => s.as_mut_str(),
+            Repr::Boxed(s) => s.as_mut_str(),
+            Repr::Static(_) => unreachable!(),
         }
     }
 
@@ -95,7 +262,7 @@ impl<const CAP: usize> SmallString<CAP> {
     /// Will return an Error if the byte slice is not utf8 compliant
     pub fn from_utf8(v: &[u8]) -> Result<Self, Utf8Error> {
         str::from_utf8(v)
-            .map(|s| ArrayString::from(s).map_or_else(|_| Self::Boxed(s.into()), Self::Inline))
+            .map(|s| InlineString::from(s).map_or_else(|_| Self::boxed(s.into()), Self::from))
     }
 
     /// Construct a `SmallString` from a `Vec<u8>`
@@ -105,11 +272,11 @@ impl<const CAP: usize> SmallString<CAP> {
     pub fn from_utf8_vec(v: Vec<u8>) -> Result<Self, FromUtf8Error> {
         String::from_utf8(v).map(|s| {
             if s.len() > CAP {
-                Self::Boxed(s)
+                Self::boxed(s)
             } else {
-                let mut astr = ArrayString::new();
+                let mut astr = InlineString::new();
                 astr.push_str(s.as_str());
-                Self::Inline(astr)
+                Self(Repr::Inline(astr))
             }
         })
     }
@@ -126,7 +293,8 @@ impl<const CAP: usize> SmallString<CAP> {
                 Ok(s) => return s.into(),
                 Err(error) => (v, error.valid_up_to(), error.error_len()),
             };
-            let mut buf = ArrayString::new();
+            // replacement characters can push the result past CAP
+            let mut buf = Self::new();
             let (valid, after_valid) = v.split_at(up_to);
             buf.push_str(unsafe { str::from_utf8_unchecked(valid) });
             buf.push('\u{FFFD}');
@@ -152,21 +320,21 @@ impl<const CAP: usize> SmallString<CAP> {
                     }
                 }
             }
-            buf.into()
+            buf
         }
     }
 
     pub fn push_str(&mut self, s: &str) {
-        match self {
-            Self::Inline(a) => {
+        match &mut self.0 {
+            Repr::Inline(a) => {
                 if a.try_push_str(s).is_err() {
-                    let mut buf: String = a.as_str().into();
+                    let mut buf = String::with_capacity(a.len() + s.len());
+                    buf.push_str(a);
                     buf.push_str(s);
-                    let mut new_a = Self::Boxed(buf);
-                    mem::swap(self, &mut new_a);
+                    *self = Self::boxed(buf);
                 }
             }
-            Self::Boxed(a) => a.push_str(s),
+            _ => self.make_boxed(s.len()).push_str(s),
         }
     }
 
@@ -180,9 +348,14 @@ impl<const CAP: usize> SmallString<CAP> {
     #[allow(clippy::missing_panics_doc)]
     #[must_use]
     pub fn split_off(&mut self, index: usize) -> Self {
-        match self {
-            Self::Boxed(s) => s.split_off(index).into(),
-            Self::Inline(s) => {
+        match &mut self.0 {
+            Repr::Boxed(_) => self.make_boxed(0).split_off(index).into(),
+            Repr::Static(s) => {
+                let (left, right) = s.split_at(index);
+                *self = Self::from_static(left);
+                Self::from_static(right)
+            }
+            Repr::Inline(s) => {
                 let st = s.as_str();
                 assert!(st.is_char_boundary(index));
                 let result = st[index..].into();
@@ -192,6 +365,262 @@ impl<const CAP: usize> SmallString<CAP> {
         }
     }
 
+    /// Appends the given char to the end of this string, moving it to the
+    /// heap if it no longer fits in `CAP` bytes.
+    pub fn push(&mut self, c: char) {
+        match &mut self.0 {
+            Repr::Inline(a) => {
+                if a.try_push(c).is_err() {
+                    self.make_boxed(c.len_utf8()).push(c);
+                }
+            }
+            _ => self.make_boxed(c.len_utf8()).push(c),
+        }
+    }
+
+    /// Removes the last character from the string and returns it, or `None`
+    /// if it is empty.
+    pub fn pop(&mut self) -> Option<char> {
+        match &mut self.0 {
+            Repr::Inline(a) => a.pop(),
+            Repr::Boxed(_) => self.make_boxed(0).pop(),
+            Repr::Static(s) => {
+                let c = s.chars().next_back()?;
+                *self = Self::from_static(&s[..s.len() - c.len_utf8()]);
+                Some(c)
+            }
+        }
+    }
+
+    /// Inserts a character at the given byte position, moving the string to
+    /// the heap if it no longer fits in `CAP` bytes.
+    ///
+    /// # Panics
+    /// Panics if `idx` is larger than the string's length, or if it does not
+    /// lie on a char boundary.
+    pub fn insert(&mut self, idx: usize, ch: char) {
+        let mut bits = [0; 4];
+        self.insert_str(idx, ch.encode_utf8(&mut bits));
+    }
+
+    /// Inserts a string slice at the given byte position, moving the string
+    /// to the heap if it no longer fits in `CAP` bytes.
+    ///
+    /// # Panics
+    /// Panics if `idx` is larger than the string's length, or if it does not
+    /// lie on a char boundary.
+    pub fn insert_str(&mut self, idx: usize, string: &str) {
+        match &mut self.0 {
+            Repr::Inline(a) if a.len() + string.len() <= CAP => {
+                assert!(a.is_char_boundary(idx));
+                let mut buf = InlineString::new();
+                buf.push_str(&a[..idx]);
+                buf.push_str(string);
+                buf.push_str(&a[idx..]);
+                *a = buf;
+            }
+            _ => self.make_boxed(string.len()).insert_str(idx, string),
+        }
+    }
+
+    /// Removes the char at the given byte position and returns it.
+    ///
+    /// # Panics
+    /// Panics if `idx` is larger than or equal to the string's length, or if
+    /// it does not lie on a char boundary.
+    pub fn remove(&mut self, idx: usize) -> char {
+        match &mut self.0 {
+            Repr::Inline(a) => a.remove(idx),
+            _ => self.make_boxed(0).remove(idx),
+        }
+    }
+
+    /// Shortens the string to `new_len` bytes, has no effect if `new_len` is
+    /// greater than the current length.
+    ///
+    /// # Panics
+    /// Panics if `new_len` does not lie on a char boundary.
+    pub fn truncate(&mut self, new_len: usize) {
+        match &mut self.0 {
+            Repr::Inline(a) => a.truncate(new_len),
+            Repr::Boxed(_) => self.make_boxed(0).truncate(new_len),
+            Repr::Static(s) => {
+                if new_len < s.len() {
+                    assert!(s.is_char_boundary(new_len));
+                    *self = Self::from_static(&s[..new_len]);
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        match &mut self.0 {
+            Repr::Inline(a) => a.clear(),
+            Repr::Boxed(_) => self.make_boxed(0).clear(),
+            Repr::Static(_) => *self = Self::new(),
+        }
+    }
+
+    /// Retains only the characters specified by the predicate.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(char) -> bool,
+    {
+        match &mut self.0 {
+            Repr::Inline(a) => {
+                let mut buf = InlineString::new();
+                for c in a.chars().filter(|c| f(*c)) {
+                    buf.push(c);
+                }
+                *a = buf;
+            }
+            _ => self.make_boxed(0).retain(f),
+        }
+    }
+
+    /// Removes the specified byte range from the string and returns the
+    /// removed characters as an iterator.
+    ///
+    /// # Panics
+    /// Panics if the start or end of the range do not lie on a char boundary,
+    /// or if they are out of bounds.
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, CAP>
+    where
+        R: RangeBounds<usize>,
+    {
+        match &mut self.0 {
+            Repr::Inline(a) => {
+                let (start, end) = range_bounds(&range, a.len());
+                let drained = InlineString::from(&a[start..end]).unwrap_or_default();
+                let mut buf = InlineString::new();
+                buf.push_str(&a[..start]);
+                buf.push_str(&a[end..]);
+                *a = buf;
+                Drain(DrainInner::Inline {
+                    end: drained.len(),
+                    start: 0,
+                    buf: drained,
+                })
+            }
+            _ => {
+                let buf = self.make_boxed(0);
+                let (start, end) = range_bounds(&range, buf.len());
+                assert!(buf.is_char_boundary(start) && buf.is_char_boundary(end));
+                Drain(DrainInner::Boxed {
+                    buf,
+                    range: (start, end),
+                    start,
+                    end,
+                })
+            }
+        }
+    }
+
+    /// Replaces the specified byte range with the given string, moving the
+    /// string to the heap if it no longer fits in `CAP` bytes.
+    ///
+    /// # Panics
+    /// Panics if the start or end of the range do not lie on a char boundary,
+    /// or if they are out of bounds.
+    pub fn replace_range<R>(&mut self, range: R, replace_with: &str)
+    where
+        R: RangeBounds<usize>,
+    {
+        match &mut self.0 {
+            Repr::Inline(a) => {
+                let (start, end) = range_bounds(&range, a.len());
+                let new_len = a.len() - (end - start) + replace_with.len();
+                if new_len <= CAP {
+                    let mut buf = InlineString::new();
+                    buf.push_str(&a[..start]);
+                    buf.push_str(replace_with);
+                    buf.push_str(&a[end..]);
+                    *a = buf;
+                } else {
+                    let additional = new_len - a.len();
+                    self.make_boxed(additional)
+                        .replace_range(start..end, replace_with);
+                }
+            }
+            _ => self.make_boxed(0).replace_range(range, replace_with),
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more bytes, moving the
+    /// string to the heap if that exceeds `CAP`.
+    pub fn reserve(&mut self, additional: usize) {
+        match &self.0 {
+            Repr::Inline(a) => {
+                if a.len() + additional > CAP {
+                    self.make_boxed(additional);
+                }
+            }
+            Repr::Boxed(_) => self.make_boxed(0).reserve(additional),
+            Repr::Static(_) => {
+                self.make_boxed(additional);
+            }
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        match &self.0 {
+            Repr::Inline(_) => CAP,
+            Repr::Boxed(s) => s.capacity(),
+            Repr::Static(s) => s.len(),
+        }
+    }
+
+    /// Moves a heap allocated string back inline if it fits in `CAP` bytes,
+    /// otherwise shrinks the heap buffer to match its length.
+    pub fn shrink_to_fit(&mut self) {
+        self.compact();
+        if self.is_boxed() {
+            self.make_boxed(0).shrink_to_fit();
+        }
+    }
+
+    /// Moves a heap allocated string back inline if it fits in `CAP` bytes,
+    /// freeing the heap buffer.
+    pub fn compact(&mut self) {
+        if let Repr::Boxed(s) = &self.0
+            && let Ok(a) = InlineString::from(s.as_str())
+        {
+            self.0 = Repr::Inline(a);
+        }
+    }
+
+    /// Returns a guard which derefs to this string and calls
+    /// [`compact`](Self::compact) when dropped, so any mutation made through
+    /// it leaves short strings inline.
+    pub fn auto_compact(&mut self) -> AutoCompact<'_, CAP> {
+        AutoCompact(self)
+    }
+
+    fn boxed(s: String) -> Self {
+        Self(Repr::Boxed(BoxedString::new(s)))
+    }
+
+    /// Move an inline or static string to the heap, reserving room for
+    /// `additional` bytes, and return the heap buffer, which is put back when
+    /// the returned guard is dropped.
+    fn make_boxed(&mut self, additional: usize) -> BoxedMut<'_, CAP> {
+        let empty = Repr::Inline(InlineString::new());
+        let buf = match mem::replace(&mut self.0, empty) {
+            Repr::Boxed(s) => s.into_string(),
+            repr => {
+                let mut buf = String::with_capacity(repr.as_str().len() + additional);
+                buf.push_str(repr.as_str());
+                buf
+            }
+        };
+        BoxedMut {
+            repr: &mut self.0,
+            buf,
+        }
+    }
+
     /// # Panics
     /// `from_display` panics if a formatting trait implementation returns an
     /// error. This indicates an incorrect implementation
@@ -205,9 +634,10 @@ impl<const CAP: usize> SmallString<CAP> {
     #[must_use]
     pub fn into_smallstring<const CAP1: usize>(self) -> SmallString<CAP1> {
         if self.len() > CAP1 {
-            match self {
-                SmallString::Boxed(s) => SmallString::Boxed(s),
-                SmallString::Inline(s) => s.as_str().into(),
+            match self.0 {
+                Repr::Boxed(s) => SmallString(Repr::Boxed(s)),
+                Repr::Static(s) => SmallString(Repr::Static(s)),
+                Repr::Inline(s) => s.as_str().into(),
             }
         } else {
             self.as_str().into()
@@ -215,9 +645,197 @@ impl<const CAP: usize> SmallString<CAP> {
     }
 }
 
+/// Convert a range into `(start, end)` byte offsets, panicking on invalid
+/// ranges the same way `String` does.
+fn range_bounds<R>(range: &R, len: usize) -> (usize, usize)
+where
+    R: RangeBounds<usize>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(&n) => n,
+        Bound::Excluded(&n) => n + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&n) => n + 1,
+        Bound::Excluded(&n) => n,
+        Bound::Unbounded => len,
+    };
+    assert!(start <= end, "range start {start} is after range end {end}");
+    assert!(
+        end <= len,
+        "range end {end} is out of bounds for length {len}"
+    );
+    (start, end)
+}
+
+/// Guard returned by [`SmallString::auto_compact`], the string is moved back
+/// inline when the guard is dropped if it fits in `CAP` bytes.
+pub struct AutoCompact<'a, const CAP: usize>(&'a mut SmallString<CAP>);
+
+impl<const CAP: usize> Deref for AutoCompact<'_, CAP> {
+    type Target = SmallString<CAP>;
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<const CAP: usize> DerefMut for AutoCompact<'_, CAP> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0
+    }
+}
+
+impl<const CAP: usize> Drop for AutoCompact<'_, CAP> {
+    fn drop(&mut self) {
+        self.0.compact();
+    }
+}
+
+/// Heap buffer of a `SmallString` taken out by `make_boxed`, it is put back
+/// when the guard is dropped.
+struct BoxedMut<'a, const CAP: usize> {
+    repr: &'a mut Repr<CAP>,
+    buf: String,
+}
+
+impl<const CAP: usize> Deref for BoxedMut<'_, CAP> {
+    type Target = String;
+    fn deref(&self) -> &Self::Target {
+        &self.buf
+    }
+}
+
+impl<const CAP: usize> DerefMut for BoxedMut<'_, CAP> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buf
+    }
+}
+
+impl<const CAP: usize> Drop for BoxedMut<'_, CAP> {
+    fn drop(&mut self) {
+        *self.repr = Repr::Boxed(BoxedString::new(mem::take(&mut self.buf)));
+    }
+}
+
+/// A draining iterator for `SmallString`, created by [`SmallString::drain`].
+pub struct Drain<'a, const CAP: usize>(DrainInner<'a, CAP>);
+
+enum DrainInner<'a, const CAP: usize> {
+    Inline {
+        buf: InlineString<CAP>,
+        start: usize,
+        end: usize,
+    },
+    /// The drained range is removed from `buf` when the iterator is dropped.
+    Boxed {
+        buf: BoxedMut<'a, CAP>,
+        range: (usize, usize),
+        start: usize,
+        end: usize,
+    },
+}
+
+impl<const CAP: usize> DrainInner<'_, CAP> {
+    fn remaining(&mut self) -> (&str, &mut usize, &mut usize) {
+        match self {
+            Self::Inline { buf, start, end } => (&buf[*start..*end], start, end),
+            Self::Boxed {
+                buf, start, end, ..
+            } => (&buf[*start..*end], start, end),
+        }
+    }
+}
+
+impl<const CAP: usize> Drain<'_, CAP> {
+    /// Returns the remaining (sub)string of this iterator as a slice.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            DrainInner::Inline { buf, start, end } => &buf[*start..*end],
+            DrainInner::Boxed {
+                buf, start, end, ..
+            } => &buf[*start..*end],
+        }
+    }
+}
+
+impl<const CAP: usize> Iterator for Drain<'_, CAP> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let (s, start, _) = self.0.remaining();
+        let c = s.chars().next()?;
+        *start += c.len_utf8();
+        Some(c)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.as_str().chars().size_hint()
+    }
+}
+
+impl<const CAP: usize> DoubleEndedIterator for Drain<'_, CAP> {
+    fn next_back(&mut self) -> Option<char> {
+        let (s, _, end) = self.0.remaining();
+        let c = s.chars().next_back()?;
+        *end -= c.len_utf8();
+        Some(c)
+    }
+}
+
+impl<const CAP: usize> Drop for Drain<'_, CAP> {
+    fn drop(&mut self) {
+        if let DrainInner::Boxed {
+            buf,
+            range: (start, end),
+            ..
+        } = &mut self.0
+        {
+            buf.replace_range(*start..*end, "");
+        }
+    }
+}
+
+impl<const CAP: usize> FusedIterator for Drain<'_, CAP> {}
+
+impl<const CAP: usize> StackStr for SmallString<CAP> {
+    fn as_str(&self) -> &str {
+        self.as_str()
+    }
+
+    fn is_inline(&self) -> bool {
+        self.is_inline()
+    }
+
+    fn from_str_lossless(s: &str) -> Self {
+        s.into()
+    }
+
+    fn from_display(buf: impl fmt::Display) -> Self {
+        Self::from_display(buf)
+    }
+
+    fn from_utf8(v: &[u8]) -> Result<Self, Utf8Error> {
+        Self::from_utf8(v)
+    }
+
+    fn from_utf8_lossy(v: &[u8]) -> Self {
+        Self::from_utf8_lossy(v)
+    }
+
+    fn split_off(&mut self, index: usize) -> Self {
+        self.split_off(index)
+    }
+
+    fn into_string(self) -> String {
+        self.into()
+    }
+}
+
 impl<const CAP: usize> From<&str> for SmallString<CAP> {
     fn from(item: &str) -> Self {
-        ArrayString::from(item).map_or_else(|e| Self::Boxed(e.element().into()), Self::Inline)
+        InlineString::from(item).map_or_else(|e| Self::boxed(e.element().into()), Self::from)
     }
 }
 
@@ -235,7 +853,7 @@ impl<'de, const CAP: usize> Deserialize<'de> for SmallString<CAP> {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_string(SmartStringVisitor(PhantomData))
+        deserializer.deserialize_str(SmartStringVisitor(PhantomData))
     }
 }
 
@@ -289,7 +907,7 @@ impl<const CAP: usize> Visitor<'_> for SmartStringVisitor<CAP> {
 impl<const CAP: usize> From<String> for SmallString<CAP> {
     fn from(item: String) -> Self {
         if item.len() > CAP {
-            Self::Boxed(item)
+            Self::boxed(item)
         } else {
             SmallString::from(item.as_str())
         }
@@ -302,17 +920,24 @@ impl<const CAP: usize> From<&String> for SmallString<CAP> {
     }
 }
 
+impl<const CAP: usize> From<InlineString<CAP>> for SmallString<CAP> {
+    fn from(item: InlineString<CAP>) -> Self {
+        Self(Repr::Inline(item))
+    }
+}
+
 impl<const CAP: usize> From<ArrayString<CAP>> for SmallString<CAP> {
     fn from(item: ArrayString<CAP>) -> Self {
-        Self::Inline(item)
+        Self(Repr::Inline(item.into()))
     }
 }
 
 impl<const CAP: usize> From<SmallString<CAP>> for String {
     fn from(item: SmallString<CAP>) -> Self {
-        match item {
-            SmallString::Inline(s) => s.to_string(),
-            SmallString::Boxed(s) => s,
+        match item.0 {
+            Repr::Inline(s) => s.to_string(),
+            Repr::Boxed(s) => s.into_string(),
+            Repr::Static(s) => s.into(),
         }
     }
 }
@@ -354,9 +979,9 @@ impl<const CAP: usize> From<StackString> for SmallString<CAP> {
     fn from(item: StackString) -> Self {
         if item.len() > CAP {
             let s: String = item.into();
-            Self::Boxed(s)
+            Self::boxed(s)
         } else {
-            Self::Inline(ArrayString::from(item.as_str()).unwrap())
+            Self(Repr::Inline(InlineString::from(item.as_str()).unwrap()))
         }
     }
 }
@@ -369,9 +994,10 @@ impl<const CAP: usize> From<&StackString> for SmallString<CAP> {
 
 impl<const CAP: usize> From<SmallString<CAP>> for StackString {
     fn from(item: SmallString<CAP>) -> Self {
-        match item {
-            SmallString::Inline(s) => StackString::from(s.as_str()),
-            SmallString::Boxed(s) => s.into(),
+        match item.0 {
+            Repr::Inline(s) => StackString::from(s.as_str()),
+            Repr::Boxed(s) => s.into_string().into(),
+            Repr::Static(s) => StackString::from_static(s),
         }
     }
 }
@@ -413,12 +1039,14 @@ impl<const CAP: usize> AsRef<[u8]> for SmallString<CAP> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<const CAP: usize> AsRef<OsStr> for SmallString<CAP> {
     fn as_ref(&self) -> &OsStr {
         self.as_str().as_ref()
     }
 }
 
+#[cfg(feature = "std")]
 impl<const CAP: usize> AsRef<Path> for SmallString<CAP> {
     fn as_ref(&self) -> &Path {
         Path::new(self)
@@ -453,7 +1081,7 @@ impl<'a, const CAP: usize> PartialEq<Cow<'a, str>> for SmallString<CAP> {
 }
 
 impl<'a, const CAP: usize> PartialOrd<Cow<'a, str>> for SmallString<CAP> {
-    fn partial_cmp(&self, other: &Cow<'a, str>) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Cow<'a, str>) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], &other[..])
     }
 }
@@ -466,7 +1094,7 @@ impl<const CAP: usize> PartialEq<String> for SmallString<CAP> {
 }
 
 impl<const CAP: usize> PartialOrd<String> for SmallString<CAP> {
-    fn partial_cmp(&self, other: &String) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &String) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], &other[..])
     }
 }
@@ -479,7 +1107,7 @@ impl<const CAP: usize> PartialEq<str> for SmallString<CAP> {
 }
 
 impl<const CAP: usize> PartialOrd<str> for SmallString<CAP> {
-    fn partial_cmp(&self, other: &str) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &str) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], other)
     }
 }
@@ -492,7 +1120,7 @@ impl<const CAP: usize> PartialEq<&str> for SmallString<CAP> {
 }
 
 impl<const CAP: usize> PartialOrd<&str> for SmallString<CAP> {
-    fn partial_cmp(&self, other: &&str) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &&str) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], &other[..])
     }
 }
@@ -541,25 +1169,136 @@ impl<const CAP: usize> ToSql for SmallString<CAP> {
     }
 }
 
-#[cfg(feature = "utoipa_types")]
-impl<const CAP: usize> PartialSchema for SmallString<CAP> {
-    fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
-        str::schema()
+#[cfg(feature = "diesel_types")]
+impl<DB, const CAP: usize> serialize::ToSql<Text, DB> for SmallString<CAP>
+where
+    DB: Backend,
+    str: serialize::ToSql<Text, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut serialize::Output<'b, '_, DB>) -> serialize::Result {
+        <str as serialize::ToSql<Text, DB>>::to_sql(self.as_str(), out)
     }
 }
 
-#[cfg(feature = "utoipa_types")]
-impl<const CAP: usize> ToSchema for SmallString<CAP> {
-    fn name() -> Cow<'static, str> {
-        str::name()
+#[cfg(feature = "diesel_types")]
+impl<DB, const CAP: usize> deserialize::FromSql<Text, DB> for SmallString<CAP>
+where
+    DB: Backend,
+    *const str: deserialize::FromSql<Text, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        let ptr = <*const str as deserialize::FromSql<Text, DB>>::from_sql(bytes)?;
+        // The pointer is only valid for the lifetime of `bytes`, the contents
+        // are copied out before returning
+        let s = unsafe { &*ptr };
+        Ok(s.into())
     }
 }
 
-#[cfg(feature = "axum_types")]
-impl<const CAP: usize> IntoResponse for SmallString<CAP> {
-    fn into_response(self) -> axum::response::Response {
-        let s: String = self.into();
-        s.into_response()
+#[cfg(feature = "sqlx_types")]
+impl<DB, const CAP: usize> sqlx::Type<DB> for SmallString<CAP>
+where
+    DB: Database,
+    str: sqlx::Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <str as sqlx::Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <str as sqlx::Type<DB>>::compatible(ty)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<'r, DB, const CAP: usize> Decode<'r, DB> for SmallString<CAP>
+where
+    DB: Database,
+    &'r str: Decode<'r, DB>,
+{
+    fn decode(value: DB::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        <&'r str as Decode<'r, DB>>::decode(value).map(Into::into)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<const CAP: usize> Encode<'_, Postgres> for SmallString<CAP> {
+    fn encode_by_ref(
+        &self,
+        buf: &mut PgArgumentBuffer,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <&str as Encode<Postgres>>::encode(self.as_str(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<const CAP: usize> Encode<'_, MySql> for SmallString<CAP> {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <&str as Encode<MySql>>::encode(self.as_str(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<'q, const CAP: usize> Encode<'q, Sqlite> for SmallString<CAP> {
+    fn encode(
+        self,
+        buf: &mut Vec<SqliteArgumentValue<'q>>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <String as Encode<Sqlite>>::encode(self.into(), buf)
+    }
+
+    fn encode_by_ref(
+        &self,
+        buf: &mut Vec<SqliteArgumentValue<'q>>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <String as Encode<Sqlite>>::encode(self.into(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<const CAP: usize> PgHasArrayType for SmallString<CAP> {
+    fn array_type_info() -> PgTypeInfo {
+        <&str as PgHasArrayType>::array_type_info()
+    }
+
+    fn array_compatible(ty: &PgTypeInfo) -> bool {
+        <&str as PgHasArrayType>::array_compatible(ty)
+    }
+}
+
+#[cfg(feature = "rusqlite_types")]
+impl<const CAP: usize> rusqlite::types::ToSql for SmallString<CAP> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(ValueRef::Text(self.as_bytes())))
+    }
+}
+
+#[cfg(feature = "rusqlite_types")]
+impl<const CAP: usize> rusqlite::types::FromSql for SmallString<CAP> {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str().map(|s| s.into())
+    }
+}
+
+#[cfg(feature = "utoipa_types")]
+impl<const CAP: usize> PartialSchema for SmallString<CAP> {
+    fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
+        str::schema()
+    }
+}
+
+#[cfg(feature = "utoipa_types")]
+impl<const CAP: usize> ToSchema for SmallString<CAP> {
+    fn name() -> Cow<'static, str> {
+        str::name()
+    }
+}
+
+#[cfg(feature = "axum_types")]
+impl<const CAP: usize> IntoResponse for SmallString<CAP> {
+    fn into_response(self) -> axum::response::Response {
+        let s: String = self.into();
+        s.into_response()
     }
 }
 
@@ -576,11 +1315,7 @@ impl<const CAP: usize> FromIterator<char> for SmallString<CAP> {
         let iter = iter.into_iter();
         let (min, max) = iter.size_hint();
         let size = if let Some(x) = max { x } else { min };
-        let mut s = if size > CAP {
-            Self::Boxed(String::with_capacity(size))
-        } else {
-            Self::Inline(ArrayString::<CAP>::new())
-        };
+        let mut s = Self::with_capacity(size);
         for c in iter {
             s.write_char(c).unwrap();
         }
@@ -588,6 +1323,109 @@ impl<const CAP: usize> FromIterator<char> for SmallString<CAP> {
     }
 }
 
+impl<'a, const CAP: usize> FromIterator<&'a str> for SmallString<CAP> {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut s = Self::new();
+        s.extend(iter);
+        s
+    }
+}
+
+impl<const CAP: usize> FromIterator<String> for SmallString<CAP> {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        let mut s = Self::new();
+        s.extend(iter);
+        s
+    }
+}
+
+impl<const CAP: usize> FromIterator<SmallString<CAP>> for SmallString<CAP> {
+    fn from_iter<I: IntoIterator<Item = SmallString<CAP>>>(iter: I) -> Self {
+        let mut iter = iter.into_iter();
+        let mut s = iter.next().unwrap_or_default();
+        for item in iter {
+            s.push_str(&item);
+        }
+        s
+    }
+}
+
+impl<const CAP: usize> Extend<char> for SmallString<CAP> {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        let (min, _) = iter.size_hint();
+        self.reserve(min);
+        for c in iter {
+            self.push(c);
+        }
+    }
+}
+
+impl<'a, const CAP: usize> Extend<&'a str> for SmallString<CAP> {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for s in iter {
+            self.push_str(s);
+        }
+    }
+}
+
+impl<const CAP: usize> Extend<String> for SmallString<CAP> {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        for s in iter {
+            // take over the allocation of a piece that would not fit
+            if self.is_empty() && s.len() > self.capacity() {
+                *self = Self::boxed(s);
+            } else {
+                self.push_str(&s);
+            }
+        }
+    }
+}
+
+impl<const CAP: usize> Add<&str> for SmallString<CAP> {
+    type Output = Self;
+
+    fn add(mut self, rhs: &str) -> Self {
+        self.push_str(rhs);
+        self
+    }
+}
+
+impl<const CAP: usize> AddAssign<&str> for SmallString<CAP> {
+    fn add_assign(&mut self, rhs: &str) {
+        self.push_str(rhs);
+    }
+}
+
+/// Format into a `SmallString`, written inline until it no longer fits in
+/// `CAP` bytes. `CAP` is either given as `format_small!(CAP; ...)` or
+/// inferred from the target type.
+#[macro_export]
+macro_rules! format_small {
+    ($cap:expr; $($arg:tt)*) => {{
+        use ::core::fmt::Write;
+        let mut buf = $crate::SmallString::<$cap>::new();
+        ::core::write!(buf, "{}", ::core::format_args!($($arg)*)).unwrap();
+        buf
+    }};
+    ($($arg:tt)*) => {{
+        use ::core::fmt::Write;
+        let mut buf = $crate::SmallString::new();
+        ::core::write!(buf, "{}", ::core::format_args!($($arg)*)).unwrap();
+        buf
+    }};
+}
+
+/// Format into an inline `SmallString<CAP>`, returning a `CapacityError`
+/// instead of moving to the heap if the result is longer than `CAP` bytes.
+#[macro_export]
+macro_rules! try_format_small {
+    ($cap:expr; $($arg:tt)*) => {
+        $crate::FixedString::<$cap>::from_display(::core::format_args!($($arg)*))
+            .map($crate::SmallString::<$cap>::from)
+    };
+}
+
 /// Allow SmallString to be used as graphql scalar value
 #[cfg(feature = "async_graphql")]
 #[Scalar]
@@ -610,15 +1448,128 @@ impl<const CAP: usize> ScalarType for SmallString<CAP> {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a, const CAP: usize> arbitrary::Arbitrary<'a> for SmallString<CAP> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        generators::arbitrary_string(u, &[CAP, crate::MAX_INLINE]).map(Into::into)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<const CAP: usize> proptest::arbitrary::Arbitrary for SmallString<CAP> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        generators::string_strategy(&[CAP, crate::MAX_INLINE])
+            .prop_map(Into::into)
+            .boxed()
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl<const CAP: usize> quickcheck::Arbitrary for SmallString<CAP> {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        generators::quickcheck_string(g, &[CAP, crate::MAX_INLINE]).into()
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let s = String::from(self.as_str());
+        Box::new(quickcheck::Arbitrary::shrink(&s).map(Into::into))
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<const CAP: usize> Archive for SmallString<CAP> {
+    type Archived = ArchivedString;
+    type Resolver = StringResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedString::resolve_from_str(self.as_str(), resolver, out);
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<S: Fallible + ?Sized, const CAP: usize> rkyv::Serialize<S> for SmallString<CAP>
+where
+    S::Error: Source,
+    str: SerializeUnsized<S>,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        ArchivedString::serialize_from_str(self.as_str(), serializer)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<D: Fallible + ?Sized, const CAP: usize> rkyv::Deserialize<SmallString<CAP>, D>
+    for ArchivedString
+where
+    str: DeserializeUnsized<str, D>,
+{
+    fn deserialize(&self, _: &mut D) -> Result<SmallString<CAP>, D::Error> {
+        Ok(self.as_str().into())
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<const CAP: usize> PartialEq<SmallString<CAP>> for ArchivedString {
+    fn eq(&self, other: &SmallString<CAP>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<const CAP: usize> PartialEq<ArchivedString> for SmallString<CAP> {
+    fn eq(&self, other: &ArchivedString) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<const CAP: usize> bincode::Encode for SmallString<CAP> {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        bincode::Encode::encode(self.as_str(), encoder)
+    }
+}
+
+/// Decodes straight into the inline buffer when the contents fit.
+#[cfg(feature = "bincode")]
+impl<Context, const CAP: usize> bincode::Decode<Context> for SmallString<CAP> {
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        binary::bincode_decode::<CAP, _, _>(decoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<'de, Context, const CAP: usize> bincode::BorrowDecode<'de, Context> for SmallString<CAP> {
+    fn borrow_decode<D: BorrowDecoder<'de, Context = Context>>(
+        decoder: &mut D,
+    ) -> Result<Self, DecodeError> {
+        bincode::Decode::decode(decoder)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<const CAP: usize> BorshSerialize for SmallString<CAP> {
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        BorshSerialize::serialize(self.as_str(), writer)
+    }
+}
+
+/// Reads straight into the inline buffer when the contents fit.
+#[cfg(feature = "borsh")]
+impl<const CAP: usize> BorshDeserialize for SmallString<CAP> {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        binary::borsh_deserialize::<CAP, _, _>(reader)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use arrayvec::ArrayString;
     use rand::{Rng, rng as thread_rng};
     use std::fmt::Write;
 
-    #[cfg(feature = "async_graphql")]
-    use std::future::Future;
-
     use crate::{small_string::SmallString, stack_string::StackString};
 
     #[test]
@@ -628,17 +1579,63 @@ mod tests {
 
     #[test]
     fn test_sizeof() {
-        if std::mem::size_of::<String>() == 24 {
-            assert_eq!(std::mem::size_of::<StackString>(), 24);
-            assert_eq!(std::mem::size_of::<SmallString<32>>(), 40);
-            assert_eq!(std::mem::size_of::<SmallString<30>>(), 40);
-            assert_eq!(std::mem::size_of::<ArrayString<32>>(), 36);
-            assert_eq!(std::mem::size_of::<[u8; 32]>(), 32);
+        if core::mem::size_of::<String>() == 24 {
+            assert_eq!(core::mem::size_of::<StackString>(), 24);
+            assert_eq!(core::mem::size_of::<SmallString<32>>(), 40);
+            assert_eq!(core::mem::size_of::<SmallString<30>>(), 32);
+            assert_eq!(core::mem::size_of::<SmallString<15>>(), 24);
+            assert_eq!(core::mem::size_of::<SmallString<8>>(), 24);
+            assert_eq!(core::mem::size_of::<SmallString<16>>(), 24);
+            assert_eq!(core::mem::size_of::<SmallString<23>>(), 24);
+            assert_eq!(core::mem::size_of::<SmallString<24>>(), 32);
+            assert_eq!(core::mem::size_of::<SmallString<100>>(), 104);
+            assert_eq!(core::mem::size_of::<SmallString<300>>(), 304);
+            assert_eq!(core::mem::size_of::<Option<SmallString<30>>>(), 32);
+            assert_eq!(core::mem::size_of::<ArrayString<32>>(), 36);
+            assert_eq!(core::mem::size_of::<[u8; 32]>(), 32);
         } else {
-            assert!(false);
+            panic!();
         }
     }
 
+    #[test]
+    fn test_from_utf8_lossy_expands_past_cap() {
+        let s = SmallString::<4>::from_utf8_lossy(&[0xff, 0xff]);
+        assert_eq!(s, "\u{FFFD}\u{FFFD}");
+        assert!(!s.is_inline());
+
+        let s = SmallString::<4>::from_utf8_lossy(b"a\xffb");
+        assert_eq!(s, "a\u{FFFD}b");
+        assert!(!s.is_inline());
+
+        let s = SmallString::<5>::from_utf8_lossy(b"a\xffb");
+        assert!(s.is_inline());
+    }
+
+    #[test]
+    fn test_large_capacity() {
+        let long = "\u{e9}".repeat(140);
+        let mut s = SmallString::<300>::from(long.as_str());
+        assert!(s.is_inline());
+        assert_eq!(s, long);
+        s.push_str("abcdefghijklmnopqrst");
+        assert!(s.is_inline());
+        assert_eq!(s.len(), 300);
+        s.push('!');
+        assert!(!s.is_inline());
+        assert_eq!(s.len(), 301);
+
+        let a = ArrayString::<300>::from(&long).unwrap();
+        let s = SmallString::from(a);
+        assert!(s.is_inline());
+        assert_eq!(s, long);
+
+        let f = crate::FixedString::<300>::try_from_str(&long).unwrap();
+        let s = SmallString::from(f);
+        assert!(s.is_inline());
+        assert_eq!(s, long);
+    }
+
     #[test]
     fn test_small_string_split_off() {
         let mut s0 = "hello there".to_string();
@@ -696,14 +1693,18 @@ mod tests {
 
     #[test]
     fn test_as_ref() {
-        use std::path::Path;
         let s = SmallString::<20>::from("Hello");
         let st: &str = s.as_ref();
         assert_eq!(st, s.as_str());
         let bt: &[u8] = s.as_ref();
         assert_eq!(bt, s.as_bytes());
-        let pt: &Path = s.as_ref();
-        assert_eq!(pt, Path::new("Hello"));
+        #[cfg(feature = "std")]
+        {
+            use std::path::Path;
+
+            let pt: &Path = s.as_ref();
+            assert_eq!(pt, Path::new("Hello"));
+        }
     }
 
     #[test]
@@ -723,6 +1724,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::cmp_owned)]
     fn test_partial_eq_string() {
         assert_eq!(SmallString::<20>::from("Hello"), String::from("Hello"));
         assert_eq!(SmallString::<20>::from("Hello"), "Hello");
@@ -734,8 +1736,8 @@ mod tests {
     fn test_from_iterator_char() {
         let mut rng = thread_rng();
         let v: Vec<char> = (0..20).map(|_| rng.random::<char>()).collect();
-        let s0: SmallString<20> = v.iter().map(|x| *x).collect();
-        let s1: String = v.iter().map(|x| *x).collect();
+        let s0: SmallString<20> = v.iter().copied().collect();
+        let s1: String = v.iter().copied().collect();
         assert_eq!(s0, s1);
     }
 
@@ -753,6 +1755,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::unnecessary_map_on_constructor)]
     fn test_equality() {
         let s: SmallString<20> = "hey".into();
         assert_eq!(Some(&s).map(Into::into), Some("hey"));
@@ -782,12 +1785,358 @@ mod tests {
         assert!(<SmallString<20> as ToSql>::accepts(&t));
         let mut buf = BytesMut::new();
         match s.to_sql(&t, &mut buf).unwrap() {
-            IsNull::Yes => assert!(false),
+            IsNull::Yes => panic!(),
             IsNull::No => {}
         }
         assert_eq!(buf.as_ref(), b"Hello There");
     }
 
+    #[cfg(feature = "diesel_types")]
+    #[test]
+    fn test_diesel_sqlite() {
+        use diesel::{
+            Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection, sql_query,
+        };
+
+        diesel::table! {
+            test_strings (id) {
+                id -> Integer,
+                s -> Text,
+                n -> Nullable<Text>,
+            }
+        }
+
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        sql_query("CREATE TABLE test_strings (id INTEGER PRIMARY KEY, s TEXT NOT NULL, n TEXT)")
+            .execute(&mut conn)
+            .unwrap();
+
+        let s = SmallString::<20>::from("Hello There");
+        diesel::insert_into(test_strings::table)
+            .values((
+                test_strings::id.eq(1),
+                test_strings::s.eq(&s),
+                test_strings::n.eq(Some(&s)),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::insert_into(test_strings::table)
+            .values((
+                test_strings::id.eq(2),
+                test_strings::s.eq(s.clone()),
+                test_strings::n.eq(None::<SmallString<20>>),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+
+        let rows: Vec<(SmallString<20>, Option<SmallString<20>>)> = test_strings::table
+            .select((test_strings::s, test_strings::n))
+            .order(test_strings::id)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(rows, vec![(s.clone(), Some(s.clone())), (s.clone(), None)]);
+
+        let found: SmallString<20> = test_strings::table
+            .select(test_strings::s)
+            .filter(test_strings::s.eq(&s))
+            .first(&mut conn)
+            .unwrap();
+        assert_eq!(found, s);
+    }
+
+    #[cfg(feature = "sqlx_types")]
+    #[tokio::test]
+    async fn test_sqlx_sqlite() {
+        use sqlx::{Connection, SqliteConnection};
+
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE test_strings (id INTEGER PRIMARY KEY, s TEXT NOT NULL, n TEXT)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let s = SmallString::<20>::from("Hello There");
+        sqlx::query("INSERT INTO test_strings (id, s, n) VALUES (1, $1, $2)")
+            .bind(&s)
+            .bind(Some(&s))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO test_strings (id, s, n) VALUES (2, $1, $2)")
+            .bind(s.clone())
+            .bind(None::<SmallString<20>>)
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let rows: Vec<(SmallString<20>, Option<SmallString<20>>)> =
+            sqlx::query_as("SELECT s, n FROM test_strings ORDER BY id")
+                .fetch_all(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(rows, vec![(s.clone(), Some(s.clone())), (s.clone(), None)]);
+    }
+
+    #[cfg(feature = "rusqlite_types")]
+    #[test]
+    fn test_rusqlite() {
+        use rusqlite::{Connection, Error, params};
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE test_strings (id INTEGER PRIMARY KEY, s TEXT NOT NULL, n TEXT)",
+            [],
+        )
+        .unwrap();
+
+        let s = SmallString::<20>::from("Hello There");
+        conn.execute(
+            "INSERT INTO test_strings (id, s, n) VALUES (1, ?1, ?2)",
+            params![s, Some(&s)],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO test_strings (id, s, n) VALUES (2, ?1, ?2)",
+            params![s, None::<SmallString<20>>],
+        )
+        .unwrap();
+
+        let mut stmt = conn
+            .prepare("SELECT s, n FROM test_strings ORDER BY id")
+            .unwrap();
+        let rows: Vec<(SmallString<20>, Option<SmallString<20>>)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows, vec![(s.clone(), Some(s.clone())), (s.clone(), None)]);
+        let s = &rows[0].0;
+        assert!(s.is_inline());
+
+        let result: Result<SmallString<20>, _> =
+            conn.query_row("SELECT CAST(X'FF' AS TEXT)", [], |row| row.get(0));
+        assert!(matches!(result, Err(Error::FromSqlConversionFailure(..))));
+    }
+
+    #[test]
+    fn test_add_extend_collect() {
+        let mut s = SmallString::<10>::from("hello") + " ";
+        assert!(s.is_inline());
+        s += "there";
+        assert!(s.is_boxed());
+        assert_eq!(s, "hello there");
+
+        let mut s = SmallString::<4>::new();
+        s.extend(['a', 'b']);
+        assert!(s.is_inline());
+        s.extend("cdef".chars());
+        assert!(s.is_boxed());
+        assert_eq!(s, "abcdef");
+
+        let s: SmallString<32> = "the quick brown fox".split(' ').collect();
+        assert!(s.is_inline());
+        assert_eq!(s, "thequickbrownfox");
+        let s: SmallString<8> = "the quick brown fox".split(' ').collect();
+        assert!(s.is_boxed());
+        assert_eq!(s, "thequickbrownfox");
+
+        let mut long = String::with_capacity(64);
+        long.push_str("a string which is too long");
+        let ptr = long.as_ptr();
+        let s: SmallString<8> = [long, String::from("!")].into_iter().collect();
+        assert!(s.is_boxed());
+        assert_eq!(s.as_ptr(), ptr);
+        assert_eq!(s, "a string which is too long!");
+        let s: SmallString<4> = ["ab", "cd", "ef", "gh", "ij"].into_iter().collect();
+        assert!(s.is_boxed());
+        assert_eq!(s, "abcdefghij");
+        let s: SmallString<4> = [""; 5].into_iter().collect();
+        assert!(s.is_inline());
+        let s: SmallString<4> = ["a", "", "b", "", "c"]
+            .map(String::from)
+            .into_iter()
+            .collect();
+        assert!(s.is_inline());
+        assert_eq!(s, "abc");
+        let mut s = SmallString::<4>::new();
+        s.extend(["a"; 4]);
+        assert!(s.is_inline());
+        let s: SmallString<4> = ["abcd"; 5].iter().map(|s| String::from(*s)).collect();
+        assert!(s.is_boxed());
+        assert_eq!(s, "abcd".repeat(5));
+        let mut s = SmallString::<4>::from("ab");
+        s.extend(["cd", "ef", "gh"]);
+        assert!(s.is_boxed());
+        assert_eq!(s, "abcdefgh");
+
+        let s: SmallString<8> = [String::from("ab"), String::from("cd")]
+            .into_iter()
+            .collect();
+        assert!(s.is_inline());
+        assert_eq!(s, "abcd");
+
+        let s: SmallString<8> = [SmallString::from_static("static str"), "ab".into()]
+            .into_iter()
+            .collect();
+        assert!(s.is_boxed());
+        assert_eq!(s, "static strab");
+        let s: SmallString<8> = std::iter::empty::<SmallString<8>>().collect();
+        assert!(s.is_inline());
+        assert!(s.is_empty());
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_rkyv() {
+        use rkyv::{rancor::Error, string::ArchivedString};
+
+        for input in ["hello", "a string which does not fit inline"] {
+            let s = SmallString::<10>::from(input);
+            let bytes = rkyv::to_bytes::<Error>(&s).unwrap();
+            let archived = rkyv::access::<ArchivedString, Error>(&bytes).unwrap();
+            assert_eq!(archived, input);
+            assert_eq!(s, *archived);
+            let t: SmallString<10> = rkyv::deserialize::<_, Error>(archived).unwrap();
+            assert_eq!(t, input);
+            assert_eq!(t.is_inline(), s.is_inline());
+        }
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bincode() {
+        let config = bincode::config::standard();
+        for input in ["", "hello", "a string which does not fit inline"] {
+            let s = SmallString::<10>::from(input);
+            let bytes = bincode::encode_to_vec(&s, config).unwrap();
+            assert_eq!(bytes, bincode::encode_to_vec(input, config).unwrap());
+            let (t, len): (SmallString<10>, _) =
+                bincode::decode_from_slice(&bytes, config).unwrap();
+            assert_eq!(len, bytes.len());
+            assert_eq!(t, input);
+            assert_eq!(t.is_inline(), input.len() <= 10);
+        }
+        let bytes = bincode::encode_to_vec(&b"ab\xff"[..], config).unwrap();
+        assert!(bincode::decode_from_slice::<SmallString<10>, _>(&bytes, config).is_err());
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh() {
+        for input in ["", "hello", "a string which does not fit inline"] {
+            let s = SmallString::<10>::from(input);
+            let bytes = borsh::to_vec(&s).unwrap();
+            assert_eq!(bytes, borsh::to_vec(input).unwrap());
+            let t: SmallString<10> = borsh::from_slice(&bytes).unwrap();
+            assert_eq!(t, input);
+            assert_eq!(t.is_inline(), input.len() <= 10);
+        }
+        let bytes = borsh::to_vec(&b"ab\xff"[..]).unwrap();
+        assert!(borsh::from_slice::<SmallString<10>>(&bytes).is_err());
+        assert!(borsh::from_slice::<SmallString<10>>(&bytes[..4]).is_err());
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn test_postcard() {
+        let mut buf = [0u8; 64];
+        for input in ["", "hello", "a string which does not fit inline"] {
+            let s = SmallString::<10>::from(input);
+            let bytes = postcard::to_slice(&s, &mut buf).unwrap();
+            let t: SmallString<10> = postcard::from_bytes(bytes).unwrap();
+            assert_eq!(t, input);
+            assert_eq!(t.is_inline(), input.len() <= 10);
+        }
+    }
+
+    #[test]
+    fn test_from_static() {
+        use std::collections::HashSet;
+
+        const SHORT: SmallString<5> = SmallString::from_static("hello");
+        const LONG: SmallString<5> = crate::sstr!(5; "hello there");
+        static TABLE: [SmallString<8>; 2] = [
+            SmallString::from_static("alpha"),
+            SmallString::from_static("alphabetical"),
+        ];
+
+        assert!(SHORT.is_inline());
+        assert!(LONG.is_static());
+        assert!(!LONG.is_boxed());
+        assert_eq!(LONG, "hello there");
+        assert_eq!(LONG, SmallString::<5>::from("hello there"));
+        assert!(TABLE[0].is_inline());
+        assert!(TABLE[1].is_static());
+
+        let set: HashSet<SmallString<8>> = TABLE.iter().cloned().collect();
+        assert!(set.contains("alphabetical"));
+        assert!(set.contains(&SmallString::from("alphabetical")));
+
+        let mut s = LONG;
+        assert_eq!(s.pop(), Some('e'));
+        assert!(s.is_static());
+        s.truncate(7);
+        assert!(s.is_static());
+        let t = s.split_off(5);
+        assert!(s.is_inline());
+        assert!(t.is_inline());
+        assert_eq!(s, "hello");
+        assert_eq!(t, " t");
+
+        let mut s = LONG;
+        let t = s.split_off(2);
+        assert!(s.is_inline());
+        assert!(t.is_static());
+        assert_eq!(t, "llo there");
+        let mut s = SmallString::<5>::from_static("hello!");
+        assert_eq!(s.pop(), Some('!'));
+        assert!(s.is_inline());
+        assert_eq!(s, "hello");
+        let mut s = LONG;
+        s.truncate(5);
+        assert!(s.is_inline());
+        assert_eq!(s, "hello");
+
+        let mut s = LONG;
+        s.push('!');
+        assert!(s.is_boxed());
+        assert_eq!(s, "hello there!");
+
+        let mut s = LONG;
+        s.as_mut_str().make_ascii_uppercase();
+        assert_eq!(s, "HELLO THERE");
+        assert_eq!(LONG, "hello there");
+
+        let s: StackString = LONG.into();
+        assert_eq!(s, "hello there");
+        let s: String = LONG.into();
+        assert_eq!(s, "hello there");
+    }
+
+    #[test]
+    fn test_format_small() {
+        let s = crate::format_small!(5; "{}{}", "ab", 1);
+        assert_eq!(s, "ab1");
+        assert!(s.is_inline());
+        let s = crate::format_small!(5; "{}-{}", "hello", "there");
+        assert_eq!(s, "hello-there");
+        assert!(s.is_boxed());
+        let x = 42;
+        let s: SmallString<10> = crate::format_small!("x = {x}");
+        assert_eq!(s, "x = 42");
+        assert!(s.is_inline());
+    }
+
+    #[test]
+    fn test_try_format_small() {
+        let s = crate::try_format_small!(5; "{}{}", "ab", 1).unwrap();
+        assert_eq!(s, "ab1");
+        assert!(s.is_inline());
+        let e = crate::try_format_small!(5; "{}-{}", "hello", "there").unwrap_err();
+        assert_eq!(e.len, 11);
+        assert_eq!(e.capacity, 5);
+    }
+
     #[test]
     fn test_from_display() {
         use std::fmt::Display;
@@ -818,6 +2167,208 @@ mod tests {
         assert!(s.is_boxed());
     }
 
+    #[test]
+    fn test_push_pop() {
+        let mut s = SmallString::<5>::from("1234");
+        s.push('5');
+        assert!(s.is_inline());
+        assert_eq!(s.pop(), Some('5'));
+        s.push('5');
+        s.push('6');
+        assert!(s.is_boxed());
+        assert_eq!(s, "123456");
+        assert_eq!(s.pop(), Some('6'));
+        assert_eq!(s, "12345");
+        let mut s = SmallString::<5>::new();
+        assert_eq!(s.pop(), None);
+        s.push('\u{1F600}');
+        s.push('\u{1F600}');
+        assert!(s.is_boxed());
+        assert_eq!(s, "\u{1F600}\u{1F600}");
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut s0 = String::from("hello");
+        let mut s1 = SmallString::<8>::from("hello");
+        s0.insert(0, 'x');
+        s1.insert(0, 'x');
+        assert_eq!(s1, s0);
+        s0.insert_str(3, "ab");
+        s1.insert_str(3, "ab");
+        assert_eq!(s1, s0);
+        assert!(s1.is_inline());
+        s0.insert_str(s0.len(), "yz");
+        s1.insert_str(s1.len(), "yz");
+        assert_eq!(s1, s0);
+        assert!(s1.is_boxed());
+        s0.insert(1, '\u{e9}');
+        s1.insert(1, '\u{e9}');
+        assert_eq!(s1, s0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_char_boundary() {
+        let mut s = SmallString::<8>::from("\u{e9}");
+        s.insert(1, 'x');
+    }
+
+    #[test]
+    fn test_remove_truncate_clear() {
+        let mut s0 = String::from("h\u{e9}llo");
+        let mut s1 = SmallString::<8>::from("h\u{e9}llo");
+        assert_eq!(s0.remove(1), s1.remove(1));
+        assert_eq!(s1, s0);
+        s0.truncate(2);
+        s1.truncate(2);
+        assert_eq!(s1, s0);
+        s1.clear();
+        assert!(s1.is_empty());
+        assert!(s1.is_inline());
+
+        let mut s = SmallString::<2>::from("hello");
+        assert_eq!(s.remove(0), 'h');
+        s.truncate(1);
+        assert_eq!(s, "e");
+        s.clear();
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut s0 = String::from("a1b2c3");
+        let mut s1 = SmallString::<8>::from("a1b2c3");
+        let mut s2 = SmallString::<2>::from("a1b2c3");
+        s0.retain(|c| c.is_alphabetic());
+        s1.retain(|c| c.is_alphabetic());
+        s2.retain(|c| c.is_alphabetic());
+        assert_eq!(s1, s0);
+        assert_eq!(s2, s0);
+        assert!(s1.is_inline());
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut s0 = String::from("hello there");
+        let mut s1 = SmallString::<20>::from("hello there");
+        let mut s2 = SmallString::<5>::from("hello there");
+        let d0: String = s0.drain(2..7).collect();
+        let d1: String = s1.drain(2..7).collect();
+        let d2: String = s2.drain(2..7).collect();
+        assert_eq!(d1, d0);
+        assert_eq!(d2, d0);
+        assert_eq!(s1, s0);
+        assert_eq!(s2, s0);
+        assert!(s1.is_inline());
+
+        let mut s = SmallString::<20>::from("abc\u{e9}");
+        let mut d = s.drain(1..);
+        assert_eq!(d.as_str(), "bc\u{e9}");
+        assert_eq!(d.next_back(), Some('\u{e9}'));
+        assert_eq!(d.next(), Some('b'));
+        assert_eq!(d.as_str(), "c");
+        drop(d);
+        assert_eq!(s, "a");
+        let d: String = s.drain(..).collect();
+        assert_eq!(d, "a");
+        assert!(s.is_empty());
+
+        let mut s = SmallString::<4>::from("abc\u{e9}xyz");
+        let mut d = s.drain(1..5);
+        assert_eq!(d.next_back(), Some('\u{e9}'));
+        assert_eq!(d.as_str(), "bc");
+        drop(d);
+        assert_eq!(s, "axyz");
+        assert!(s.is_boxed());
+    }
+
+    #[test]
+    fn test_boxed_panic_leaves_string_unchanged() {
+        let mut s = SmallString::<4>::from("h\u{e9}llo");
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| s.insert(2, 'x')));
+        assert!(result.is_err());
+        assert_eq!(s, "h\u{e9}llo");
+        assert!(s.is_boxed());
+    }
+
+    #[test]
+    fn test_debug_send_sync() {
+        fn is_send_sync<T: Send + Sync>() {}
+        is_send_sync::<SmallString<8>>();
+
+        assert_eq!(format!("{:?}", SmallString::<8>::from("a\"b")), r#""a\"b""#);
+        let s = SmallString::<4>::from("too long");
+        assert_eq!(format!("{s:?}"), format!("{:?}", "too long"));
+    }
+
+    #[test]
+    fn test_replace_range() {
+        let mut s0 = String::from("hello there");
+        let mut s1 = SmallString::<12>::from("hello there");
+        s0.replace_range(..5, "goodbye");
+        s1.replace_range(..5, "goodbye");
+        assert_eq!(s1, s0);
+        assert!(s1.is_boxed());
+
+        let mut s0 = String::from("hello there");
+        let mut s1 = SmallString::<12>::from("hello there");
+        s0.replace_range(6..=10, "you");
+        s1.replace_range(6..=10, "you");
+        assert_eq!(s1, s0);
+        assert!(s1.is_inline());
+    }
+
+    #[test]
+    fn test_reserve_capacity() {
+        let mut s = SmallString::<10>::from("hello");
+        assert_eq!(s.capacity(), 10);
+        s.reserve(5);
+        assert!(s.is_inline());
+        s.reserve(6);
+        assert!(s.is_boxed());
+        assert!(s.capacity() >= 11);
+        assert_eq!(s, "hello");
+        s.push_str(" there");
+        s.shrink_to_fit();
+        assert!(s.is_boxed());
+        assert_eq!(s.capacity(), 11);
+        s.truncate(5);
+        s.shrink_to_fit();
+        assert!(s.is_inline());
+        assert_eq!(s.capacity(), 10);
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut s = SmallString::<10>::from("hello there");
+        assert!(s.is_boxed());
+        s.compact();
+        assert!(s.is_boxed());
+        let t = s.split_off(5);
+        assert!(s.is_boxed());
+        assert!(t.is_inline());
+        s.compact();
+        assert!(s.is_inline());
+        assert_eq!(s, "hello");
+
+        let mut s = SmallString::<10>::from("hello there");
+        {
+            let mut guard = s.auto_compact();
+            guard.truncate(8);
+            assert!(guard.is_boxed());
+        }
+        assert!(s.is_inline());
+        assert_eq!(s, "hello th");
+
+        let mut s = SmallString::<10>::from("hello");
+        s.auto_compact().push_str(" there");
+        assert!(s.is_boxed());
+        s.auto_compact().drain(5..).for_each(drop);
+        assert!(s.is_inline());
+        assert_eq!(s, "hello");
+    }
+
     #[test]
     fn test_into_smallstring() {
         let mut s = SmallString::<10>::new();
@@ -877,16 +2428,13 @@ mod tests {
             type Value = SmallString<CAP>;
             type Error = Infallible;
 
-            fn load(
+            async fn load(
                 &self,
                 _: &[SmallString<CAP>],
-            ) -> impl Future<Output = Result<HashMap<SmallString<CAP>, Self::Value>, Self::Error>>
-            {
-                async move {
-                    let mut m = HashMap::new();
-                    m.insert("HELLO".into(), "WORLD".into());
-                    Ok(m)
-                }
+            ) -> Result<HashMap<SmallString<CAP>, Self::Value>, Self::Error> {
+                let mut m = HashMap::new();
+                m.insert("HELLO".into(), "WORLD".into());
+                Ok(m)
             }
         }
 
@@ -1,20 +1,33 @@
-use derive_more::{Deref, DerefMut, Display, From, Index, IndexMut, Into};
-use serde::{Deserialize, Serialize};
-use smartstring::alias::String as SmartStringInner;
-use std::{
-    borrow::{Borrow, BorrowMut, Cow},
+use alloc::{
+    borrow::Cow,
+    string::{FromUtf8Error, String},
+    vec::Vec,
+};
+use core::{
+    borrow::{Borrow, BorrowMut},
     convert::Infallible,
-    ffi::OsStr,
     fmt::{self, Write as FmtWrite},
     iter::FromIterator,
-    path::Path,
+    ops::{Add, AddAssign},
     str,
     str::{FromStr, Utf8Error},
-    string::FromUtf8Error,
 };
+use derive_more::{Deref, DerefMut, Display, From, Index, IndexMut, Into};
+use serde::{Deserialize, Serialize};
+use smartstring::alias::String as SmartStringInner;
+#[cfg(feature = "std")]
+use std::{ffi::OsStr, path::Path};
 
 use smartstring::MAX_INLINE;
 
+use crate::{SmallCow, SmallString, StackCow, StackString, stack_str::StackStr};
+
+#[cfg(any(feature = "arbitrary", feature = "proptest", feature = "quickcheck"))]
+use crate::generators;
+
+#[cfg(feature = "proptest")]
+use proptest::strategy::{BoxedStrategy, Strategy};
+
 #[cfg(feature = "postgres_types")]
 use bytes::BytesMut;
 #[cfg(feature = "postgres_types")]
@@ -29,6 +42,26 @@ use axum::response::IntoResponse;
 #[cfg(feature = "axum_types")]
 use axum::body::Body;
 
+#[cfg(feature = "diesel_types")]
+use diesel::{
+    backend::Backend,
+    deserialize::{self, FromSqlRow},
+    expression::AsExpression,
+    serialize,
+    sql_types::Text,
+};
+
+#[cfg(feature = "sqlx_types")]
+use sqlx::{
+    Database, Decode, Encode, MySql, Postgres, Sqlite,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo},
+    sqlite::SqliteArgumentValue,
+};
+
+#[cfg(feature = "rusqlite_types")]
+use rusqlite::types::{FromSqlResult, ToSqlOutput, ValueRef};
+
 #[cfg(feature = "async_graphql")]
 use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
 
@@ -51,6 +84,8 @@ use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value
     PartialOrd,
     Ord,
 )]
+#[cfg_attr(feature = "diesel_types", derive(AsExpression, FromSqlRow))]
+#[cfg_attr(feature = "diesel_types", diesel(sql_type = Text))]
 pub struct SmartString(SmartStringInner);
 
 impl SmartString {
@@ -138,6 +173,40 @@ impl SmartString {
     }
 }
 
+impl StackStr for SmartString {
+    fn as_str(&self) -> &str {
+        self.as_str()
+    }
+
+    fn is_inline(&self) -> bool {
+        self.0.is_inline()
+    }
+
+    fn from_str_lossless(s: &str) -> Self {
+        s.into()
+    }
+
+    fn from_display(buf: impl fmt::Display) -> Self {
+        Self::from_display(buf)
+    }
+
+    fn from_utf8(v: &[u8]) -> Result<Self, Utf8Error> {
+        Self::from_utf8(v)
+    }
+
+    fn from_utf8_lossy(v: &[u8]) -> Self {
+        Self::from_utf8_lossy(v)
+    }
+
+    fn split_off(&mut self, index: usize) -> Self {
+        self.split_off(index)
+    }
+
+    fn into_string(self) -> String {
+        self.into()
+    }
+}
+
 impl From<SmartString> for String {
     fn from(item: SmartString) -> Self {
         item.0.into()
@@ -174,6 +243,76 @@ impl From<&str> for SmartString {
     }
 }
 
+impl From<StackString> for SmartString {
+    fn from(item: StackString) -> Self {
+        if item.is_inline() {
+            item.as_str().into()
+        } else {
+            String::from(item).into()
+        }
+    }
+}
+
+impl From<SmartString> for StackString {
+    fn from(item: SmartString) -> Self {
+        if item.0.is_inline() {
+            item.as_str().into()
+        } else {
+            String::from(item).into()
+        }
+    }
+}
+
+impl<const CAP: usize> From<SmallString<CAP>> for SmartString {
+    fn from(item: SmallString<CAP>) -> Self {
+        if item.is_boxed() {
+            String::from(item).into()
+        } else {
+            item.as_str().into()
+        }
+    }
+}
+
+impl<const CAP: usize> From<SmartString> for SmallString<CAP> {
+    fn from(item: SmartString) -> Self {
+        if item.0.is_inline() {
+            item.as_str().into()
+        } else {
+            String::from(item).into()
+        }
+    }
+}
+
+impl From<StackCow<'_>> for SmartString {
+    fn from(item: StackCow<'_>) -> Self {
+        match item {
+            StackCow::Borrowed(s) => s.into(),
+            StackCow::Owned(s) => s.into(),
+        }
+    }
+}
+
+impl From<SmartString> for StackCow<'_> {
+    fn from(item: SmartString) -> Self {
+        Self::Owned(item.into())
+    }
+}
+
+impl<const CAP: usize> From<SmallCow<'_, CAP>> for SmartString {
+    fn from(item: SmallCow<'_, CAP>) -> Self {
+        match item {
+            SmallCow::Borrowed(s) => s.into(),
+            SmallCow::Owned(s) => s.into(),
+        }
+    }
+}
+
+impl<const CAP: usize> From<SmartString> for SmallCow<'_, CAP> {
+    fn from(item: SmartString) -> Self {
+        Self::Owned(item.into())
+    }
+}
+
 impl<'a> From<&'a SmartString> for &'a str {
     fn from(item: &SmartString) -> &str {
         item.as_str()
@@ -219,12 +358,14 @@ impl AsRef<[u8]> for SmartString {
     }
 }
 
+#[cfg(feature = "std")]
 impl AsRef<OsStr> for SmartString {
     fn as_ref(&self) -> &OsStr {
         self.as_str().as_ref()
     }
 }
 
+#[cfg(feature = "std")]
 impl AsRef<Path> for SmartString {
     fn as_ref(&self) -> &Path {
         Path::new(self)
@@ -246,7 +387,7 @@ impl<'a> PartialEq<Cow<'a, str>> for SmartString {
 }
 
 impl<'a> PartialOrd<Cow<'a, str>> for SmartString {
-    fn partial_cmp(&self, other: &Cow<'a, str>) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Cow<'a, str>) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], &other[..])
     }
 }
@@ -259,7 +400,7 @@ impl PartialEq<String> for SmartString {
 }
 
 impl PartialOrd<String> for SmartString {
-    fn partial_cmp(&self, other: &String) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &String) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], &other[..])
     }
 }
@@ -272,7 +413,7 @@ impl PartialEq<str> for SmartString {
 }
 
 impl PartialOrd<str> for SmartString {
-    fn partial_cmp(&self, other: &str) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &str) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], other)
     }
 }
@@ -285,7 +426,7 @@ impl<'a> PartialEq<&'a str> for SmartString {
 }
 
 impl<'a> PartialOrd<&'a str> for SmartString {
-    fn partial_cmp(&self, other: &&'a str) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &&'a str) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], &other[..])
     }
 }
@@ -297,19 +438,19 @@ impl PartialEq<SmartString> for str {
 }
 
 impl PartialOrd<SmartString> for str {
-    fn partial_cmp(&self, other: &SmartString) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &SmartString) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(self, &other[..])
     }
 }
 
-impl<'a> PartialEq<SmartString> for &'a str {
+impl PartialEq<SmartString> for &str {
     fn eq(&self, other: &SmartString) -> bool {
         PartialEq::eq(&self[..], &other[..])
     }
 }
 
-impl<'a> PartialOrd<SmartString> for &'a str {
-    fn partial_cmp(&self, other: &SmartString) -> Option<std::cmp::Ordering> {
+impl PartialOrd<SmartString> for &str {
+    fn partial_cmp(&self, other: &SmartString) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], &other[..])
     }
 }
@@ -321,6 +462,57 @@ impl FromIterator<char> for SmartString {
     }
 }
 
+impl<'a> FromIterator<&'a str> for SmartString {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        Self(SmartStringInner::from_iter(iter))
+    }
+}
+
+impl FromIterator<String> for SmartString {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        Self(SmartStringInner::from_iter(iter))
+    }
+}
+
+impl FromIterator<SmartString> for SmartString {
+    fn from_iter<I: IntoIterator<Item = SmartString>>(iter: I) -> Self {
+        Self(iter.into_iter().map(|s| s.0).collect())
+    }
+}
+
+impl Extend<char> for SmartString {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl<'a> Extend<&'a str> for SmartString {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl Extend<String> for SmartString {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl Add<&str> for SmartString {
+    type Output = Self;
+
+    fn add(mut self, rhs: &str) -> Self {
+        self.push_str(rhs);
+        self
+    }
+}
+
+impl AddAssign<&str> for SmartString {
+    fn add_assign(&mut self, rhs: &str) {
+        self.push_str(rhs);
+    }
+}
+
 #[cfg(feature = "postgres_types")]
 impl<'a> FromSql<'a> for SmartString {
     fn from_sql(
@@ -365,6 +557,117 @@ impl ToSql for SmartString {
     }
 }
 
+#[cfg(feature = "diesel_types")]
+impl<DB> serialize::ToSql<Text, DB> for SmartString
+where
+    DB: Backend,
+    str: serialize::ToSql<Text, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut serialize::Output<'b, '_, DB>) -> serialize::Result {
+        <str as serialize::ToSql<Text, DB>>::to_sql(self.as_str(), out)
+    }
+}
+
+#[cfg(feature = "diesel_types")]
+impl<DB> deserialize::FromSql<Text, DB> for SmartString
+where
+    DB: Backend,
+    *const str: deserialize::FromSql<Text, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        let ptr = <*const str as deserialize::FromSql<Text, DB>>::from_sql(bytes)?;
+        // The pointer is only valid for the lifetime of `bytes`, the contents
+        // are copied out before returning
+        let s = unsafe { &*ptr };
+        Ok(s.into())
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<DB> sqlx::Type<DB> for SmartString
+where
+    DB: Database,
+    str: sqlx::Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <str as sqlx::Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <str as sqlx::Type<DB>>::compatible(ty)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<'r, DB> Decode<'r, DB> for SmartString
+where
+    DB: Database,
+    &'r str: Decode<'r, DB>,
+{
+    fn decode(value: DB::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        <&'r str as Decode<'r, DB>>::decode(value).map(Into::into)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl Encode<'_, Postgres> for SmartString {
+    fn encode_by_ref(
+        &self,
+        buf: &mut PgArgumentBuffer,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <&str as Encode<Postgres>>::encode(self.as_str(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl Encode<'_, MySql> for SmartString {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <&str as Encode<MySql>>::encode(self.as_str(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<'q> Encode<'q, Sqlite> for SmartString {
+    fn encode(
+        self,
+        buf: &mut Vec<SqliteArgumentValue<'q>>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <String as Encode<Sqlite>>::encode(self.into(), buf)
+    }
+
+    fn encode_by_ref(
+        &self,
+        buf: &mut Vec<SqliteArgumentValue<'q>>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <String as Encode<Sqlite>>::encode(self.into(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl PgHasArrayType for SmartString {
+    fn array_type_info() -> PgTypeInfo {
+        <&str as PgHasArrayType>::array_type_info()
+    }
+
+    fn array_compatible(ty: &PgTypeInfo) -> bool {
+        <&str as PgHasArrayType>::array_compatible(ty)
+    }
+}
+
+#[cfg(feature = "rusqlite_types")]
+impl rusqlite::types::ToSql for SmartString {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(ValueRef::Text(self.as_bytes())))
+    }
+}
+
+#[cfg(feature = "rusqlite_types")]
+impl rusqlite::types::FromSql for SmartString {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str().map(|s| s.into())
+    }
+}
+
 #[cfg(feature = "utoipa_types")]
 impl PartialSchema for SmartString {
     fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
@@ -398,9 +701,9 @@ impl From<SmartString> for Body {
 #[macro_export]
 macro_rules! format_smartstr {
     ($($arg:tt)*) => {{
-        use std::fmt::Write;
+        use ::core::fmt::Write;
         let mut buf = $crate::SmartString::new();
-        std::write!(buf, "{}", std::format_args!($($arg)*)).unwrap();
+        ::core::write!(buf, "{}", ::core::format_args!($($arg)*)).unwrap();
         buf
     }}
 }
@@ -427,13 +730,41 @@ impl ScalarType for SmartString {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SmartString {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        generators::arbitrary_string(u, &[MAX_INLINE]).map(Into::into)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for SmartString {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        generators::string_strategy(&[MAX_INLINE])
+            .prop_map(Into::into)
+            .boxed()
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl quickcheck::Arbitrary for SmartString {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        generators::quickcheck_string(g, &[MAX_INLINE]).into()
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let s = String::from(self.as_str());
+        Box::new(quickcheck::Arbitrary::shrink(&s).map(Into::into))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rand::{Rng, rng as thread_rng};
 
-    #[cfg(feature = "async_graphql")]
-    use std::future::Future;
-
     use crate::SmartString;
 
     #[test]
@@ -541,6 +872,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::cmp_owned)]
     fn test_partial_eq_string() {
         assert_eq!(SmartString::from("Hello"), String::from("Hello"));
         assert_eq!(SmartString::from("Hello"), "Hello");
@@ -553,8 +885,8 @@ mod tests {
     fn test_from_iterator_char() {
         let mut rng = thread_rng();
         let v: Vec<char> = (0..20).map(|_| rng.random::<char>()).collect();
-        let s0: SmartString = v.iter().map(|x| *x).collect();
-        let s1: String = v.iter().map(|x| *x).collect();
+        let s0: SmartString = v.iter().copied().collect();
+        let s1: String = v.iter().copied().collect();
         assert_eq!(s0, s1);
     }
 
@@ -572,6 +904,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::unnecessary_map_on_constructor)]
     fn test_equality() {
         let s: SmartString = "hey".into();
         assert_eq!(Some(&s).map(Into::into), Some("hey"));
@@ -601,12 +934,139 @@ mod tests {
         assert!(<SmartString as ToSql>::accepts(&t));
         let mut buf = BytesMut::new();
         match s.to_sql(&t, &mut buf).unwrap() {
-            IsNull::Yes => assert!(false),
+            IsNull::Yes => panic!(),
             IsNull::No => {}
         }
         assert_eq!(buf.as_ref(), b"Hello There");
     }
 
+    #[cfg(feature = "diesel_types")]
+    #[test]
+    fn test_diesel_sqlite() {
+        use diesel::{
+            Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection, sql_query,
+        };
+
+        diesel::table! {
+            test_strings (id) {
+                id -> Integer,
+                s -> Text,
+                n -> Nullable<Text>,
+            }
+        }
+
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        sql_query("CREATE TABLE test_strings (id INTEGER PRIMARY KEY, s TEXT NOT NULL, n TEXT)")
+            .execute(&mut conn)
+            .unwrap();
+
+        let s = SmartString::from("Hello There");
+        diesel::insert_into(test_strings::table)
+            .values((
+                test_strings::id.eq(1),
+                test_strings::s.eq(&s),
+                test_strings::n.eq(Some(&s)),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::insert_into(test_strings::table)
+            .values((
+                test_strings::id.eq(2),
+                test_strings::s.eq(s.clone()),
+                test_strings::n.eq(None::<SmartString>),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+
+        let rows: Vec<(SmartString, Option<SmartString>)> = test_strings::table
+            .select((test_strings::s, test_strings::n))
+            .order(test_strings::id)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(rows, vec![(s.clone(), Some(s.clone())), (s.clone(), None)]);
+
+        let found: SmartString = test_strings::table
+            .select(test_strings::s)
+            .filter(test_strings::s.eq(&s))
+            .first(&mut conn)
+            .unwrap();
+        assert_eq!(found, s);
+    }
+
+    #[cfg(feature = "sqlx_types")]
+    #[tokio::test]
+    async fn test_sqlx_sqlite() {
+        use sqlx::{Connection, SqliteConnection};
+
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE test_strings (id INTEGER PRIMARY KEY, s TEXT NOT NULL, n TEXT)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let s = SmartString::from("Hello There");
+        sqlx::query("INSERT INTO test_strings (id, s, n) VALUES (1, $1, $2)")
+            .bind(&s)
+            .bind(Some(&s))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO test_strings (id, s, n) VALUES (2, $1, $2)")
+            .bind(s.clone())
+            .bind(None::<SmartString>)
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let rows: Vec<(SmartString, Option<SmartString>)> =
+            sqlx::query_as("SELECT s, n FROM test_strings ORDER BY id")
+                .fetch_all(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(rows, vec![(s.clone(), Some(s.clone())), (s.clone(), None)]);
+    }
+
+    #[cfg(feature = "rusqlite_types")]
+    #[test]
+    fn test_rusqlite() {
+        use rusqlite::{Connection, Error, params};
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE test_strings (id INTEGER PRIMARY KEY, s TEXT NOT NULL, n TEXT)",
+            [],
+        )
+        .unwrap();
+
+        let s = SmartString::from("Hello There");
+        conn.execute(
+            "INSERT INTO test_strings (id, s, n) VALUES (1, ?1, ?2)",
+            params![s, Some(&s)],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO test_strings (id, s, n) VALUES (2, ?1, ?2)",
+            params![s, None::<SmartString>],
+        )
+        .unwrap();
+
+        let mut stmt = conn
+            .prepare("SELECT s, n FROM test_strings ORDER BY id")
+            .unwrap();
+        let rows: Vec<(SmartString, Option<SmartString>)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows, vec![(s.clone(), Some(s.clone())), (s.clone(), None)]);
+        let s = &rows[0].0;
+        assert!(s.is_inline());
+
+        let result: Result<SmartString, _> =
+            conn.query_row("SELECT CAST(X'FF' AS TEXT)", [], |row| row.get(0));
+        assert!(matches!(result, Err(Error::FromSqlConversionFailure(..))));
+    }
+
     #[test]
     fn test_from_display() {
         use std::fmt::Display;
@@ -638,7 +1098,7 @@ mod tests {
         v.extend_from_slice("yes".as_bytes());
         let s = SmartString::from_utf8_lossy(&v);
         assert_eq!(s.len(), 20);
-        assert_eq!(s.is_inline(), true);
+        assert!(s.is_inline());
     }
 
     #[test]
@@ -675,7 +1135,6 @@ mod tests {
             Context, EmptyMutation, EmptySubscription, Object, Schema,
             dataloader::{DataLoader, Loader},
         };
-        use async_trait::async_trait;
         use std::{collections::HashMap, convert::Infallible};
 
         struct SmartStringLoader;
@@ -686,21 +1145,17 @@ mod tests {
             }
         }
 
-        #[async_trait]
         impl Loader<SmartString> for SmartStringLoader {
             type Value = SmartString;
             type Error = Infallible;
 
-            fn load(
+            async fn load(
                 &self,
                 _: &[SmartString],
-            ) -> impl Future<Output = Result<HashMap<SmartString, Self::Value>, Self::Error>>
-            {
-                async move {
-                    let mut m = HashMap::new();
-                    m.insert("HELLO".into(), "WORLD".into());
-                    Ok(m)
-                }
+            ) -> Result<HashMap<SmartString, Self::Value>, Self::Error> {
+                let mut m = HashMap::new();
+                m.insert("HELLO".into(), "WORLD".into());
+                Ok(m)
             }
         }
 
@@ -735,4 +1190,21 @@ mod tests {
         std::fs::write("sdl_file_stackstring.txt", &sdl).unwrap();
         assert_eq!(&sdl, expected_sdl);
     }
+
+    #[test]
+    fn test_add_extend_collect() {
+        let mut s = SmartString::from("hello") + " there";
+        s += "!";
+        s.extend([' ', 'a']);
+        s.extend([" b"]);
+        s.extend([String::from(" c")]);
+        assert_eq!(s, "hello there! a b c");
+
+        let s: SmartString = ["a", "b"].into_iter().collect();
+        assert_eq!(s, "ab");
+        let s: SmartString = [String::from("a"), String::from("b")].into_iter().collect();
+        assert_eq!(s, "ab");
+        let s: SmartString = ["x", "y"].into_iter().map(SmartString::from).collect();
+        assert_eq!(s, "xy");
+    }
 }
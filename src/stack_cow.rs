@@ -1,18 +1,47 @@
-use derive_more::Display;
-use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
-use std::{
-    borrow::{Borrow, Cow},
+use alloc::{
+    borrow::Cow,
+    string::{FromUtf8Error, String},
+    vec::Vec,
+};
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
     convert::Infallible,
-    ffi::OsStr,
     fmt::{self, Write as FmtWrite},
+    hash::{Hash, Hasher},
     iter::FromIterator,
-    ops::Deref,
-    path::Path,
-    str::FromStr,
-    string::FromUtf8Error,
+    ops::{Add, AddAssign, Deref},
+    str::{self, FromStr, Utf8Error},
+};
+use derive_more::Display;
+use serde::{
+    self, Deserialize, Deserializer, Serialize, Serializer,
+    de::{Error, Unexpected, Visitor},
+};
+#[cfg(feature = "std")]
+use std::{ffi::OsStr, path::Path};
+
+use crate::{small_string::SmallString, stack_str::StackStr, stack_string::StackString};
+
+#[cfg(feature = "bincode")]
+use bincode::{
+    de::{BorrowDecoder, Decoder},
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+};
+
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[cfg(feature = "rkyv")]
+use rkyv::{
+    Archive, DeserializeUnsized, Place, SerializeUnsized,
+    rancor::{Fallible, Source},
+    string::{ArchivedString, StringResolver},
 };
 
-use crate::stack_string::StackString;
+#[cfg(feature = "proptest")]
+use proptest::strategy::{BoxedStrategy, Strategy};
 
 #[cfg(feature = "postgres_types")]
 use bytes::BytesMut;
@@ -28,12 +57,63 @@ use axum::response::IntoResponse;
 #[cfg(feature = "axum_types")]
 use axum::body::Body;
 
-#[derive(Display, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg(feature = "diesel_types")]
+use diesel::{
+    backend::Backend,
+    deserialize::{self, FromSqlRow},
+    expression::AsExpression,
+    serialize,
+    sql_types::Text,
+};
+
+#[cfg(feature = "sqlx_types")]
+use sqlx::{
+    Database, Decode, Encode, MySql, Postgres, Sqlite,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo},
+    sqlite::SqliteArgumentValue,
+};
+
+#[cfg(feature = "rusqlite_types")]
+use rusqlite::types::{FromSqlResult, ToSqlOutput, ValueRef};
+
+#[cfg(feature = "async_graphql")]
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
+
+#[derive(Display, Debug, Clone)]
+#[cfg_attr(feature = "diesel_types", derive(AsExpression, FromSqlRow))]
+#[cfg_attr(feature = "diesel_types", diesel(sql_type = Text))]
 pub enum StackCow<'a> {
     Borrowed(&'a str),
     Owned(StackString),
 }
 
+impl PartialEq for StackCow<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for StackCow<'_> {}
+
+impl PartialOrd for StackCow<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for StackCow<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for StackCow<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
 impl Default for StackCow<'_> {
     fn default() -> Self {
         Self::new()
@@ -80,17 +160,54 @@ impl<'a> StackCow<'a> {
         }
     }
 
-    /// Construct a `StackCow` from a `Vec<u8>`
+    /// Returns a mutable reference to the owned string, converting a
+    /// borrowed value into an owned one first, like `Cow::to_mut`.
+    pub fn to_mut(&mut self) -> &mut StackString {
+        if let Self::Borrowed(s) = *self {
+            *self = Self::Owned(s.into());
+        }
+        match self {
+            Self::Borrowed(_) => unreachable!(),
+            Self::Owned(o) => o,
+        }
+    }
+
+    /// Appends a string slice, a borrowed value is only converted to an
+    /// owned one if `s` is not empty.
+    pub fn push_str(&mut self, s: &str) {
+        if !s.is_empty() {
+            self.to_mut().push_str(s);
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.to_mut().push(c);
+    }
+
+    /// Construct a `StackCow` from a `&[u8]`, borrowing the input
     /// # Errors
     ///
     /// Will return an Error if the byte slice is not utf8 compliant
-    pub fn from_utf8(vec: Vec<u8>) -> Result<Self, FromUtf8Error> {
+    pub fn from_utf8(v: &'a [u8]) -> Result<Self, Utf8Error> {
+        str::from_utf8(v).map(Self::Borrowed)
+    }
+
+    /// Construct a `StackCow` from a `Vec<u8>`
+    /// # Errors
+    ///
+    /// Will return an Error if the `Vec<u8>` not utf8 compliant
+    pub fn from_utf8_vec(vec: Vec<u8>) -> Result<Self, FromUtf8Error> {
         String::from_utf8(vec).map(Into::into)
     }
 
+    /// Borrows the input if it is valid utf8, otherwise replaces invalid
+    /// sequences with `U+FFFD` in an owned `StackString`
     #[must_use]
     pub fn from_utf8_lossy(v: &'a [u8]) -> Self {
-        StackString::from_utf8_lossy(v).into()
+        match str::from_utf8(v) {
+            Ok(s) => Self::Borrowed(s),
+            Err(_) => Self::Owned(StackString::from_utf8_lossy(v)),
+        }
     }
 
     /// # Panics
@@ -104,6 +221,50 @@ impl<'a> StackCow<'a> {
     }
 }
 
+impl StackStr for StackCow<'_> {
+    fn as_str(&self) -> &str {
+        self.as_str()
+    }
+
+    fn is_inline(&self) -> bool {
+        match self {
+            Self::Borrowed(_) => false,
+            Self::Owned(o) => o.is_inline(),
+        }
+    }
+
+    fn from_str_lossless(s: &str) -> Self {
+        Self::Owned(s.into())
+    }
+
+    fn from_display(buf: impl fmt::Display) -> Self {
+        Self::from_display(buf)
+    }
+
+    fn from_utf8(v: &[u8]) -> Result<Self, Utf8Error> {
+        StackString::from_utf8(v).map(Self::Owned)
+    }
+
+    fn from_utf8_lossy(v: &[u8]) -> Self {
+        Self::Owned(StackString::from_utf8_lossy(v))
+    }
+
+    fn split_off(&mut self, index: usize) -> Self {
+        match self {
+            Self::Borrowed(s) => {
+                let (left, right) = s.split_at(index);
+                *s = left;
+                Self::Borrowed(right)
+            }
+            Self::Owned(o) => Self::Owned(o.split_off(index)),
+        }
+    }
+
+    fn into_string(self) -> String {
+        self.into()
+    }
+}
+
 impl Deref for StackCow<'_> {
     type Target = str;
 
@@ -124,12 +285,91 @@ impl Serialize for StackCow<'_> {
     }
 }
 
-impl<'de> Deserialize<'de> for StackCow<'_> {
-    fn deserialize<D>(deserializer: D) -> Result<StackCow<'static>, D::Error>
+impl<'de: 'a, 'a> Deserialize<'de> for StackCow<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        StackString::deserialize(deserializer).map(Into::into)
+        deserializer.deserialize_str(StackCowVisitor)
+    }
+}
+
+impl StackCow<'static> {
+    /// Deserialize into an owned `StackCow`, for use where the input does not
+    /// outlive the result, e.g. `#[serde(deserialize_with =
+    /// "StackCow::deserialize_owned")]`
+    /// # Errors
+    ///
+    /// Will return an Error if the deserializer does not produce a string
+    pub fn deserialize_owned<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <StackString as Deserialize>::deserialize(deserializer).map(Into::into)
+    }
+}
+
+struct StackCowVisitor;
+
+impl<'de> Visitor<'de> for StackCowVisitor {
+    type Value = StackCow<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(StackCow::Borrowed(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(StackCow::Owned(v.into()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(StackCow::Owned(v.into()))
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        match str::from_utf8(v) {
+            Ok(s) => Ok(StackCow::Borrowed(s)),
+            Err(_) => Err(Error::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        match str::from_utf8(v) {
+            Ok(s) => Ok(StackCow::Owned(s.into())),
+            Err(_) => Err(Error::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        match String::from_utf8(v) {
+            Ok(s) => Ok(StackCow::Owned(s.into())),
+            Err(e) => Err(Error::invalid_value(
+                Unexpected::Bytes(&e.into_bytes()),
+                &self,
+            )),
+        }
     }
 }
 
@@ -148,6 +388,21 @@ impl<'a> From<StackCow<'a>> for StackString {
     }
 }
 
+impl<const CAP: usize> From<SmallString<CAP>> for StackCow<'_> {
+    fn from(item: SmallString<CAP>) -> Self {
+        Self::Owned(item.into())
+    }
+}
+
+impl<const CAP: usize> From<StackCow<'_>> for SmallString<CAP> {
+    fn from(item: StackCow<'_>) -> Self {
+        match item {
+            StackCow::Borrowed(s) => s.into(),
+            StackCow::Owned(s) => s.into(),
+        }
+    }
+}
+
 impl<'a> From<Cow<'a, str>> for StackCow<'a> {
     fn from(item: Cow<'a, str>) -> Self {
         match item {
@@ -214,12 +469,14 @@ impl AsRef<[u8]> for StackCow<'_> {
     }
 }
 
+#[cfg(feature = "std")]
 impl AsRef<OsStr> for StackCow<'_> {
     fn as_ref(&self) -> &OsStr {
         self.as_str().as_ref()
     }
 }
 
+#[cfg(feature = "std")]
 impl AsRef<Path> for StackCow<'_> {
     fn as_ref(&self) -> &Path {
         Path::new(self)
@@ -241,7 +498,7 @@ impl<'a> PartialEq<Cow<'a, str>> for StackCow<'_> {
 }
 
 impl<'a> PartialOrd<Cow<'a, str>> for StackCow<'_> {
-    fn partial_cmp(&self, other: &Cow<'a, str>) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Cow<'a, str>) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], &other[..])
     }
 }
@@ -254,7 +511,7 @@ impl PartialEq<String> for StackCow<'_> {
 }
 
 impl PartialOrd<String> for StackCow<'_> {
-    fn partial_cmp(&self, other: &String) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &String) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], &other[..])
     }
 }
@@ -286,20 +543,91 @@ impl FromIterator<char> for StackCow<'_> {
     }
 }
 
+impl fmt::Write for StackCow<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl Extend<char> for StackCow<'_> {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter().peekable();
+        if iter.peek().is_some() {
+            self.to_mut().extend(iter);
+        }
+    }
+}
+
+impl<'b> Extend<&'b str> for StackCow<'_> {
+    fn extend<I: IntoIterator<Item = &'b str>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter().filter(|s| !s.is_empty()).peekable();
+        if iter.peek().is_some() {
+            self.to_mut().extend(iter);
+        }
+    }
+}
+
+impl Extend<String> for StackCow<'_> {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        for s in iter {
+            self.push_str(&s);
+        }
+    }
+}
+
+impl<'b> FromIterator<&'b str> for StackCow<'_> {
+    fn from_iter<I: IntoIterator<Item = &'b str>>(iter: I) -> Self {
+        Self::Owned(StackString::from_iter(iter))
+    }
+}
+
+impl FromIterator<String> for StackCow<'_> {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        Self::Owned(StackString::from_iter(iter))
+    }
+}
+
+impl<'a> FromIterator<StackCow<'a>> for StackCow<'a> {
+    fn from_iter<I: IntoIterator<Item = StackCow<'a>>>(iter: I) -> Self {
+        let mut iter = iter.into_iter();
+        let mut s = iter.next().unwrap_or_default();
+        for item in iter {
+            s.push_str(&item);
+        }
+        s
+    }
+}
+
+impl Add<&str> for StackCow<'_> {
+    type Output = Self;
+
+    fn add(mut self, rhs: &str) -> Self {
+        self.push_str(rhs);
+        self
+    }
+}
+
+impl AddAssign<&str> for StackCow<'_> {
+    fn add_assign(&mut self, rhs: &str) {
+        self.push_str(rhs);
+    }
+}
+
 impl PartialOrd<str> for StackCow<'_> {
-    fn partial_cmp(&self, other: &str) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &str) -> Option<core::cmp::Ordering> {
         self.as_str().partial_cmp(other)
     }
 }
 
 impl PartialOrd<&str> for StackCow<'_> {
-    fn partial_cmp(&self, other: &&str) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &&str) -> Option<core::cmp::Ordering> {
         self.as_str().partial_cmp(*other)
     }
 }
 
 impl<'a> PartialOrd<StackCow<'a>> for &str {
-    fn partial_cmp(&self, other: &StackCow<'a>) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &StackCow<'a>) -> Option<core::cmp::Ordering> {
         self.partial_cmp(&other.as_str())
     }
 }
@@ -348,6 +676,119 @@ impl<'a> ToSql for StackCow<'a> {
     }
 }
 
+#[cfg(feature = "diesel_types")]
+impl<DB> serialize::ToSql<Text, DB> for StackCow<'_>
+where
+    DB: Backend,
+    str: serialize::ToSql<Text, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut serialize::Output<'b, '_, DB>) -> serialize::Result {
+        <str as serialize::ToSql<Text, DB>>::to_sql(self.as_str(), out)
+    }
+}
+
+#[cfg(feature = "diesel_types")]
+impl<DB> deserialize::FromSql<Text, DB> for StackCow<'_>
+where
+    DB: Backend,
+    *const str: deserialize::FromSql<Text, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        let ptr = <*const str as deserialize::FromSql<Text, DB>>::from_sql(bytes)?;
+        // The pointer is only valid for the lifetime of `bytes`, so the
+        // contents must be copied into an owned value before returning
+        let s = unsafe { &*ptr };
+        Ok(Self::Owned(s.into()))
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<DB> sqlx::Type<DB> for StackCow<'_>
+where
+    DB: Database,
+    str: sqlx::Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <str as sqlx::Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <str as sqlx::Type<DB>>::compatible(ty)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<'r, DB> Decode<'r, DB> for StackCow<'r>
+where
+    DB: Database,
+    &'r str: Decode<'r, DB>,
+{
+    fn decode(value: DB::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        <&'r str as Decode<'r, DB>>::decode(value).map(Self::Borrowed)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl Encode<'_, Postgres> for StackCow<'_> {
+    fn encode_by_ref(
+        &self,
+        buf: &mut PgArgumentBuffer,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <&str as Encode<Postgres>>::encode(self.as_str(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl Encode<'_, MySql> for StackCow<'_> {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <&str as Encode<MySql>>::encode(self.as_str(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<'q> Encode<'q, Sqlite> for StackCow<'_> {
+    fn encode(
+        self,
+        buf: &mut Vec<SqliteArgumentValue<'q>>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <String as Encode<Sqlite>>::encode(self.into(), buf)
+    }
+
+    fn encode_by_ref(
+        &self,
+        buf: &mut Vec<SqliteArgumentValue<'q>>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <String as Encode<Sqlite>>::encode(self.into(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl PgHasArrayType for StackCow<'_> {
+    fn array_type_info() -> PgTypeInfo {
+        <&str as PgHasArrayType>::array_type_info()
+    }
+
+    fn array_compatible(ty: &PgTypeInfo) -> bool {
+        <&str as PgHasArrayType>::array_compatible(ty)
+    }
+}
+
+#[cfg(feature = "rusqlite_types")]
+impl rusqlite::types::ToSql for StackCow<'_> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(ValueRef::Text(self.as_bytes())))
+    }
+}
+
+#[cfg(feature = "rusqlite_types")]
+impl rusqlite::types::FromSql for StackCow<'_> {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        // The value is only borrowed for the duration of this call, so the
+        // result must be owned
+        value.as_str().map(|s| Self::Owned(s.into()))
+    }
+}
+
 #[cfg(feature = "utoipa_types")]
 impl<'a> PartialSchema for StackCow<'a> {
     fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
@@ -378,6 +819,173 @@ impl<'a> From<StackCow<'a>> for Body {
     }
 }
 
+/// Format into a `StackCow<'static>`, or a `SmallCow<'static, CAP>` with
+/// `format_cow!(CAP; ...)`. A format string without any arguments is
+/// returned as `Borrowed` without copying.
+#[macro_export]
+macro_rules! format_cow {
+    ($cap:expr; $($arg:tt)*) => {
+        match ::core::format_args!($($arg)*) {
+            args => match args.as_str() {
+                Some(s) => $crate::SmallCow::<'static, $cap>::Borrowed(s),
+                None => $crate::SmallCow::Owned($crate::format_small!($cap; "{}", args)),
+            },
+        }
+    };
+    ($($arg:tt)*) => {
+        match ::core::format_args!($($arg)*) {
+            args => match args.as_str() {
+                Some(s) => $crate::StackCow::<'static>::Borrowed(s),
+                None => $crate::StackCow::Owned($crate::format_sstr!("{}", args)),
+            },
+        }
+    };
+}
+
+/// Allow StackCow to be used as graphql scalar value
+#[cfg(feature = "async_graphql")]
+#[Scalar]
+impl<'a> ScalarType for StackCow<'a> {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        if let Value::String(s) = value {
+            let s: StackCow<'a> = s.into();
+            Ok(s)
+        } else {
+            Err(InputValueError::expected_type(value))
+        }
+    }
+
+    fn is_valid(value: &Value) -> bool {
+        matches!(value, Value::String(_))
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+/// Generates both borrowed and owned values.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for StackCow<'a> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        if u.arbitrary()? {
+            u.arbitrary().map(Self::Borrowed)
+        } else {
+            u.arbitrary().map(Self::Owned)
+        }
+    }
+}
+
+/// Only generates owned values.
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for StackCow<'static> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        proptest::arbitrary::any::<StackString>()
+            .prop_map(Self::Owned)
+            .boxed()
+    }
+}
+
+/// Only generates owned values.
+#[cfg(feature = "quickcheck")]
+impl quickcheck::Arbitrary for StackCow<'static> {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        Self::Owned(quickcheck::Arbitrary::arbitrary(g))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let s = StackString::from(self.as_str());
+        Box::new(quickcheck::Arbitrary::shrink(&s).map(Self::Owned))
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl Archive for StackCow<'_> {
+    type Archived = ArchivedString;
+    type Resolver = StringResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedString::resolve_from_str(self.as_str(), resolver, out);
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<S: Fallible + ?Sized> rkyv::Serialize<S> for StackCow<'_>
+where
+    S::Error: Source,
+    str: SerializeUnsized<S>,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        ArchivedString::serialize_from_str(self.as_str(), serializer)
+    }
+}
+
+/// Archived values are always deserialized as `StackCow::Owned`.
+#[cfg(feature = "rkyv")]
+impl<'a, D: Fallible + ?Sized> rkyv::Deserialize<StackCow<'a>, D> for ArchivedString
+where
+    str: DeserializeUnsized<str, D>,
+{
+    fn deserialize(&self, _: &mut D) -> Result<StackCow<'a>, D::Error> {
+        Ok(StackCow::Owned(self.as_str().into()))
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<'a> PartialEq<StackCow<'a>> for ArchivedString {
+    fn eq(&self, other: &StackCow<'a>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl PartialEq<ArchivedString> for StackCow<'_> {
+    fn eq(&self, other: &ArchivedString) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl bincode::Encode for StackCow<'_> {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        bincode::Encode::encode(self.as_str(), encoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<Context> bincode::Decode<Context> for StackCow<'_> {
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        bincode::Decode::decode(decoder).map(Self::Owned)
+    }
+}
+
+/// Borrows from the input, like the serde `Deserialize` impl.
+#[cfg(feature = "bincode")]
+impl<'de, Context> bincode::BorrowDecode<'de, Context> for StackCow<'de> {
+    fn borrow_decode<D: BorrowDecoder<'de, Context = Context>>(
+        decoder: &mut D,
+    ) -> Result<Self, DecodeError> {
+        bincode::BorrowDecode::borrow_decode(decoder).map(Self::Borrowed)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl BorshSerialize for StackCow<'_> {
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        BorshSerialize::serialize(self.as_str(), writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl BorshDeserialize for StackCow<'_> {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        BorshDeserialize::deserialize_reader(reader).map(Self::Owned)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rand::{Rng, rng as thread_rng};
@@ -395,16 +1003,27 @@ mod tests {
         let mut rng = thread_rng();
         let v: Vec<_> = (0..20).map(|_| rng.random::<u8>() & 0x7f).collect();
         let s0 = String::from_utf8(v.clone()).unwrap();
-        let s1 = StackCow::from_utf8(v).unwrap();
+        let s1 = StackCow::from_utf8(&v).unwrap();
         assert_eq!(s0.as_str(), s1.as_str());
+        assert!(s1.is_borrowed());
+        let s1 = StackCow::from_utf8_vec(v).unwrap();
+        assert_eq!(s0.as_str(), s1.as_str());
+        assert!(s1.is_owned());
 
         let v: Vec<_> = (0..20).map(|_| rng.random::<u8>()).collect();
         let s0 = String::from_utf8(v.clone());
-        let s1 = StackCow::from_utf8(v);
+        let s1 = StackCow::from_utf8(&v);
+        let s2 = StackCow::from_utf8_vec(v.clone());
 
         match s0 {
-            Ok(s) => assert_eq!(s.as_str(), s1.unwrap().as_str()),
-            Err(e) => assert_eq!(e, s1.unwrap_err()),
+            Ok(s) => {
+                assert_eq!(s.as_str(), s1.unwrap().as_str());
+                assert_eq!(s.as_str(), s2.unwrap().as_str());
+            }
+            Err(e) => {
+                assert_eq!(e.utf8_error(), s1.unwrap_err());
+                assert_eq!(e, s2.unwrap_err());
+            }
         }
     }
 
@@ -434,15 +1053,18 @@ mod tests {
 
     #[test]
     fn test_as_ref() {
-        use std::path::Path;
-
         let s = StackCow::from("Hello");
         let st: &str = s.as_ref();
         assert_eq!(st, s.as_str());
         let bt: &[u8] = s.as_ref();
         assert_eq!(bt, s.as_bytes());
-        let pt: &Path = s.as_ref();
-        assert_eq!(pt, Path::new("Hello"));
+        #[cfg(feature = "std")]
+        {
+            use std::path::Path;
+
+            let pt: &Path = s.as_ref();
+            assert_eq!(pt, Path::new("Hello"));
+        }
     }
 
     #[test]
@@ -465,6 +1087,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::cmp_owned)]
     fn test_partial_eq_string() {
         assert_eq!(StackCow::from("Hello"), String::from("Hello"));
         assert_eq!(StackCow::from("Hello"), "Hello");
@@ -477,8 +1100,8 @@ mod tests {
     fn test_from_iterator_char() {
         let mut rng = thread_rng();
         let v: Vec<char> = (0..20).map(|_| rng.random::<char>()).collect();
-        let s0: StackCow = v.iter().map(|x| *x).collect();
-        let s1: String = v.iter().map(|x| *x).collect();
+        let s0: StackCow = v.iter().copied().collect();
+        let s1: String = v.iter().copied().collect();
         assert_eq!(s0, s1);
     }
 
@@ -496,11 +1119,30 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::unnecessary_map_on_constructor)]
     fn test_equality() {
         let s: StackCow = "hey".into();
         assert_eq!(Some(&s).map(Into::into), Some("hey"));
     }
 
+    #[test]
+    fn test_borrowed_owned_eq_hash() {
+        use std::collections::HashSet;
+
+        let borrowed = StackCow::Borrowed("alpha");
+        let owned = StackCow::Owned("alpha".into());
+        assert_eq!(borrowed, owned);
+        assert!(StackCow::Borrowed("beta") > owned);
+        assert!(StackCow::Owned("beta".into()) > borrowed);
+
+        let set: HashSet<StackCow> = [borrowed].into_iter().collect();
+        assert!(set.contains(&owned));
+        assert!(set.contains("alpha"));
+        let set: HashSet<StackCow> = [owned].into_iter().collect();
+        assert!(set.contains(&StackCow::Borrowed("alpha")));
+        assert!(set.contains("alpha"));
+    }
+
     #[cfg(feature = "postgres_types")]
     use bytes::BytesMut;
     #[cfg(feature = "postgres_types")]
@@ -525,12 +1167,268 @@ mod tests {
         assert!(<StackCow as ToSql>::accepts(&t));
         let mut buf = BytesMut::new();
         match s.to_sql(&t, &mut buf).unwrap() {
-            IsNull::Yes => assert!(false),
+            IsNull::Yes => panic!(),
             IsNull::No => {}
         }
         assert_eq!(buf.as_ref(), b"Hello There");
     }
 
+    #[cfg(feature = "diesel_types")]
+    #[test]
+    fn test_diesel_sqlite() {
+        use diesel::{
+            Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection, sql_query,
+        };
+
+        diesel::table! {
+            test_strings (id) {
+                id -> Integer,
+                s -> Text,
+                n -> Nullable<Text>,
+            }
+        }
+
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        sql_query("CREATE TABLE test_strings (id INTEGER PRIMARY KEY, s TEXT NOT NULL, n TEXT)")
+            .execute(&mut conn)
+            .unwrap();
+
+        let s = StackCow::from("Hello There");
+        diesel::insert_into(test_strings::table)
+            .values((
+                test_strings::id.eq(1),
+                test_strings::s.eq(&s),
+                test_strings::n.eq(Some(&s)),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::insert_into(test_strings::table)
+            .values((
+                test_strings::id.eq(2),
+                test_strings::s.eq(s.clone()),
+                test_strings::n.eq(None::<StackCow<'static>>),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+
+        let rows: Vec<(StackCow<'static>, Option<StackCow<'static>>)> = test_strings::table
+            .select((test_strings::s, test_strings::n))
+            .order(test_strings::id)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].0.is_owned());
+        assert_eq!(rows[0].0.as_str(), s.as_str());
+        assert_eq!(rows[0].1.as_ref().map(StackCow::as_str), Some(s.as_str()));
+        assert_eq!(rows[1].0.as_str(), s.as_str());
+        assert!(rows[1].1.is_none());
+
+        let found: StackCow<'static> = test_strings::table
+            .select(test_strings::s)
+            .filter(test_strings::s.eq(&s))
+            .first(&mut conn)
+            .unwrap();
+        assert_eq!(found.as_str(), s.as_str());
+    }
+
+    #[cfg(feature = "sqlx_types")]
+    #[tokio::test]
+    async fn test_sqlx_sqlite() {
+        use sqlx::{Connection, Row, SqliteConnection};
+
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE test_strings (id INTEGER PRIMARY KEY, s TEXT NOT NULL, n TEXT)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let s = StackCow::from("Hello There");
+        sqlx::query("INSERT INTO test_strings (id, s, n) VALUES (1, $1, $2)")
+            .bind(&s)
+            .bind(Some(&s))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO test_strings (id, s, n) VALUES (2, $1, $2)")
+            .bind(s.clone())
+            .bind(None::<StackCow>)
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let rows = sqlx::query("SELECT s, n FROM test_strings ORDER BY id")
+            .fetch_all(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        let s0: StackCow = rows[0].try_get(0).unwrap();
+        let n0: Option<StackCow> = rows[0].try_get(1).unwrap();
+        let n1: Option<StackCow> = rows[1].try_get(1).unwrap();
+        assert!(s0.is_borrowed());
+        assert_eq!(s0, s);
+        assert_eq!(n0, Some(s.clone()));
+        assert_eq!(n1, None);
+    }
+
+    #[cfg(feature = "rusqlite_types")]
+    #[test]
+    fn test_rusqlite() {
+        use rusqlite::{Connection, Error, params};
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE test_strings (id INTEGER PRIMARY KEY, s TEXT NOT NULL, n TEXT)",
+            [],
+        )
+        .unwrap();
+
+        let s = StackCow::from(StackString::from("Hello There"));
+        conn.execute(
+            "INSERT INTO test_strings (id, s, n) VALUES (1, ?1, ?2)",
+            params![s, Some(&s)],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO test_strings (id, s, n) VALUES (2, ?1, ?2)",
+            params![s, None::<StackCow>],
+        )
+        .unwrap();
+
+        let mut stmt = conn
+            .prepare("SELECT s, n FROM test_strings ORDER BY id")
+            .unwrap();
+        let rows: Vec<(StackCow, Option<StackCow>)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows, vec![(s.clone(), Some(s.clone())), (s.clone(), None)]);
+        let s = &rows[0].0;
+        assert!(s.is_owned());
+
+        let result: Result<StackCow, _> =
+            conn.query_row("SELECT CAST(X'FF' AS TEXT)", [], |row| row.get(0));
+        assert!(matches!(result, Err(Error::FromSqlConversionFailure(..))));
+    }
+
+    #[test]
+    fn test_to_mut() {
+        let input = String::from("hello");
+        let mut s: StackCow = input.as_str().into();
+        s.push_str("");
+        assert!(s.is_borrowed());
+        s.to_mut().make_ascii_uppercase();
+        assert!(s.is_owned());
+        assert_eq!(s, "HELLO");
+        s.push(' ');
+        s.push_str("there");
+        assert_eq!(s, "HELLO there");
+
+        let mut s: StackCow = StackString::from("a").into();
+        s.to_mut().push('b');
+        assert_eq!(s, "ab");
+    }
+
+    #[test]
+    fn test_write_extend() {
+        use std::fmt::Write;
+
+        let input = String::from("hello");
+        let mut s: StackCow = input.as_str().into();
+        s.extend(std::iter::empty::<char>());
+        s.extend(["", ""]);
+        write!(s, "").unwrap();
+        assert!(s.is_borrowed());
+        s.extend([' ', 'a']);
+        assert!(s.is_owned());
+        s.extend(["", "nd", " more"]);
+        write!(s, " {}", 1).unwrap();
+        assert_eq!(s, "hello and more 1");
+    }
+
+    #[test]
+    fn test_add_extend_collect() {
+        let input = String::from("hello");
+        let mut s: StackCow = input.as_str().into();
+        s += "";
+        s.extend(Vec::<String>::new());
+        assert!(s.is_borrowed());
+        let mut s = s + " there";
+        s.extend([String::from("!")]);
+        assert!(s.is_owned());
+        assert_eq!(s, "hello there!");
+
+        let s: StackCow = ["a", "b"].into_iter().collect();
+        assert_eq!(s, "ab");
+        let s: StackCow = [String::from("a"), String::from("b")].into_iter().collect();
+        assert_eq!(s, "ab");
+        let s: StackCow = [StackCow::from(input.as_str()), StackCow::default()]
+            .into_iter()
+            .collect();
+        assert!(s.is_borrowed());
+        let s: StackCow = [StackCow::from(input.as_str()), " world".into()]
+            .into_iter()
+            .collect();
+        assert!(s.is_owned());
+        assert_eq!(s, "hello world");
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_rkyv() {
+        use rkyv::{rancor::Error, string::ArchivedString};
+
+        let input = String::from("hello");
+        let s = StackCow::from(input.as_str());
+        let bytes = rkyv::to_bytes::<Error>(&s).unwrap();
+        let archived = rkyv::access::<ArchivedString, Error>(&bytes).unwrap();
+        assert_eq!(archived, "hello");
+        assert_eq!(s, *archived);
+        let t: StackCow = rkyv::deserialize::<_, Error>(archived).unwrap();
+        assert!(t.is_owned());
+        assert_eq!(t, s);
+    }
+
+    #[cfg(all(feature = "bincode", feature = "borsh"))]
+    #[test]
+    fn test_bincode_borsh() {
+        let config = bincode::config::standard();
+        let s = StackCow::from("hello");
+        let bytes = bincode::encode_to_vec(&s, config).unwrap();
+        let (t, _): (StackCow, _) = bincode::borrow_decode_from_slice(&bytes, config).unwrap();
+        assert!(t.is_borrowed());
+        assert_eq!(t, "hello");
+        let (t, _): (StackCow, _) = bincode::decode_from_slice(&bytes, config).unwrap();
+        assert!(t.is_owned());
+        assert_eq!(t, "hello");
+
+        let bytes = borsh::to_vec(&s).unwrap();
+        let t: StackCow = borsh::from_slice(&bytes).unwrap();
+        assert_eq!(t, "hello");
+    }
+
+    #[test]
+    fn test_format_cow() {
+        let s = crate::format_cow!("hello {{there}}");
+        assert!(s.is_borrowed());
+        assert_eq!(s, "hello {there}");
+        let x = 42;
+        let s = crate::format_cow!("x = {x}");
+        assert!(s.is_owned());
+        assert_eq!(s, "x = 42");
+        let s = crate::format_cow!("{}-{}", "a", 1);
+        assert_eq!(s, "a-1");
+
+        let s = crate::format_cow!(5; "hello");
+        assert!(s.is_borrowed());
+        let n = 123;
+        let s = crate::format_cow!(5; "{}", n);
+        assert!(s.is_owned());
+        assert_eq!(s, "123");
+        let s: crate::SmallString<5> = s.into();
+        assert!(s.is_inline());
+    }
+
     #[test]
     fn test_from_display() {
         use std::fmt::Display;
@@ -550,16 +1448,20 @@ mod tests {
 
     #[test]
     fn test_from_utf8_lossy() {
+        let s = StackCow::from_utf8_lossy(b"this is a test");
+        assert!(s.is_borrowed());
+        assert_eq!(s, "this is a test");
+
         let mut v = Vec::new();
         v.extend_from_slice("this is a test".as_bytes());
         v.push(0xff);
         v.extend_from_slice("yes".as_bytes());
         let s = StackCow::from_utf8_lossy(&v);
         assert_eq!(s.len(), 20);
-        assert_eq!(s.is_owned(), true);
+        assert!(s.is_owned());
         let s: StackString = s.into();
         assert_eq!(s.len(), 20);
-        assert_eq!(s.is_inline(), true);
+        assert!(s.is_inline());
     }
 
     #[test]
@@ -574,6 +1476,7 @@ mod tests {
 
         #[derive(Deserialize)]
         struct A<'a> {
+            #[serde(borrow)]
             a: StackCow<'a>,
         }
 
@@ -585,5 +1488,86 @@ mod tests {
         let a: A = serde_json::from_str(s).unwrap();
         let b: B = serde_json::from_str(s).unwrap();
         assert_eq!(a.a.as_str(), b.a.as_str());
+        assert!(a.a.is_borrowed());
+
+        let s = r#"{"a": "b\nc"}"#;
+        let a: A = serde_json::from_str(s).unwrap();
+        assert_eq!(a.a.as_str(), "b\nc");
+        assert!(a.a.is_owned());
+    }
+
+    #[test]
+    fn test_deserialize_owned() {
+        #[derive(Deserialize)]
+        struct C {
+            #[serde(deserialize_with = "StackCow::deserialize_owned")]
+            a: StackCow<'static>,
+        }
+
+        let c: C = serde_json::from_reader(r#"{"a": "b"}"#.as_bytes()).unwrap();
+        assert_eq!(c.a.as_str(), "b");
+        assert!(c.a.is_owned());
+    }
+
+    #[cfg(feature = "async_graphql")]
+    #[test]
+    fn test_stackcow_async_graphql() {
+        use async_graphql::{
+            Context, EmptyMutation, EmptySubscription, Object, Schema,
+            dataloader::{DataLoader, Loader},
+        };
+        use std::{collections::HashMap, convert::Infallible};
+
+        struct StackCowLoader;
+
+        impl StackCowLoader {
+            fn new() -> Self {
+                Self
+            }
+        }
+
+        // keyed by StackString, a StackCow<'static> key trips the
+        // higher-ranked Send check on the resolver future
+        impl Loader<StackString> for StackCowLoader {
+            type Value = StackCow<'static>;
+            type Error = Infallible;
+
+            async fn load(
+                &self,
+                _: &[StackString],
+            ) -> Result<HashMap<StackString, Self::Value>, Self::Error> {
+                let mut m = HashMap::new();
+                m.insert("HELLO".into(), "WORLD".into());
+                Ok(m)
+            }
+        }
+
+        struct QueryRoot;
+
+        #[Object]
+        impl QueryRoot {
+            async fn hello<'a>(
+                &self,
+                ctx: &Context<'a>,
+            ) -> Result<Option<StackCow<'static>>, Infallible> {
+                let hello = ctx
+                    .data::<DataLoader<StackCowLoader>>()
+                    .unwrap()
+                    .load_one("hello".into())
+                    .await
+                    .unwrap();
+                Ok(hello)
+            }
+        }
+
+        let expected_sdl = include_str!("../tests/data/sdl_file_stackcow.txt");
+
+        let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .data(DataLoader::new(StackCowLoader::new(), tokio::task::spawn))
+            .finish();
+        let sdl = schema.sdl();
+
+        std::fs::write("sdl_file_stackcow.txt", &sdl).unwrap();
+        assert_eq!(&sdl, expected_sdl);
     }
 }
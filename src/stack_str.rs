@@ -0,0 +1,158 @@
+use alloc::string::String;
+use core::{fmt, str::Utf8Error};
+
+/// Operations shared by the string types in this crate, so that code can be
+/// written generically over the representation.
+///
+/// `FixedString` does not implement this trait since it can not be
+/// constructed from arbitrary input without failing.
+pub trait StackStr: Sized + AsRef<str> + fmt::Display {
+    fn as_str(&self) -> &str;
+
+    /// Returns true if the contents are stored inline, borrowed values and
+    /// static references from `from_static` are never inline.
+    fn is_inline(&self) -> bool;
+
+    /// Construct from a `&str`, this never fails or truncates the input.
+    fn from_str_lossless(s: &str) -> Self;
+
+    /// # Panics
+    /// `from_display` panics if a formatting trait implementation returns an
+    /// error.
+    fn from_display(buf: impl fmt::Display) -> Self;
+
+    /// Construct from a `&[u8]`
+    /// # Errors
+    ///
+    /// Will return an Error if the byte slice is not utf8 compliant
+    fn from_utf8(v: &[u8]) -> Result<Self, Utf8Error>;
+
+    fn from_utf8_lossy(v: &[u8]) -> Self;
+
+    /// Split the string into two at the given index, returning the content to
+    /// the right of the index.
+    ///
+    /// # Panics
+    /// Panics if the index doesn't fall on a UTF-8 character boundary.
+    #[must_use]
+    fn split_off(&mut self, index: usize) -> Self;
+
+    fn into_string(self) -> String;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SmallCow, SmallString, StackCow, StackStr, StackString};
+
+    fn check<T: StackStr>() {
+        let mut s = T::from_str_lossless("hello there");
+        assert_eq!(StackStr::as_str(&s), "hello there");
+        let t = StackStr::split_off(&mut s, 5);
+        assert_eq!(StackStr::as_str(&s), "hello");
+        assert_eq!(StackStr::as_str(&t), " there");
+        assert_eq!(t.into_string(), String::from(" there"));
+
+        let s = T::from_display(12345);
+        assert_eq!(s.as_ref(), "12345");
+
+        let s = <T as StackStr>::from_utf8(b"hello").unwrap();
+        assert_eq!(s.to_string(), "hello");
+        assert!(<T as StackStr>::from_utf8(b"\xff").is_err());
+
+        let s = <T as StackStr>::from_utf8_lossy(b"a\xffb");
+        assert_eq!(StackStr::as_str(&s), "a\u{FFFD}b");
+
+        let s = T::from_str_lossless("a");
+        assert!(StackStr::is_inline(&s));
+        let s = T::from_str_lossless(&"a".repeat(100));
+        assert!(!StackStr::is_inline(&s));
+    }
+
+    #[test]
+    fn test_stack_str() {
+        check::<StackString>();
+        check::<SmallString<10>>();
+        check::<StackCow>();
+        check::<SmallCow<10>>();
+        #[cfg(feature = "smart_string")]
+        check::<crate::SmartString>();
+    }
+
+    #[test]
+    fn test_borrowed_split_off() {
+        let input = String::from("hello there");
+        let mut s = StackCow::from(input.as_str());
+        let t = StackStr::split_off(&mut s, 5);
+        assert!(s.is_borrowed());
+        assert!(t.is_borrowed());
+        assert_eq!(s, "hello");
+        assert_eq!(t, " there");
+        assert!(!StackStr::is_inline(&s));
+    }
+
+    fn round_trip<A, B>()
+    where
+        A: StackStr + From<B>,
+        B: StackStr + From<A>,
+    {
+        for input in [
+            "short",
+            "fifteen chars!!",
+            &"a long string which will not fit inline".repeat(4),
+        ] {
+            let a = A::from_str_lossless(input);
+            let inline = StackStr::is_inline(&a);
+            let b: B = a.into();
+            assert_eq!(StackStr::as_str(&b), input);
+            let a: A = b.into();
+            assert_eq!(StackStr::as_str(&a), input);
+            assert_eq!(StackStr::is_inline(&a), inline);
+        }
+    }
+
+    #[test]
+    fn test_conversion_round_trip() {
+        round_trip::<StackString, SmallString<10>>();
+        round_trip::<SmallString<10>, StackString>();
+        round_trip::<StackString, StackCow>();
+        round_trip::<StackString, SmallCow<10>>();
+        round_trip::<SmallString<10>, StackCow>();
+        round_trip::<SmallString<10>, SmallCow<10>>();
+        round_trip::<StackCow, SmallCow<10>>();
+        round_trip::<SmallCow<10>, StackCow>();
+        #[cfg(feature = "smart_string")]
+        {
+            use crate::SmartString;
+
+            round_trip::<StackString, SmartString>();
+            round_trip::<SmartString, StackString>();
+            round_trip::<SmallString<10>, SmartString>();
+            round_trip::<SmartString, SmallString<10>>();
+            round_trip::<StackCow, SmartString>();
+            round_trip::<SmallCow<10>, SmartString>();
+        }
+    }
+
+    #[test]
+    fn test_conversion_reuses_buffer() {
+        let s: SmallString<10> = "a long string which will not fit inline".into();
+        let ptr = s.as_ptr();
+        let s: StackString = s.into();
+        assert_eq!(s.as_ptr(), ptr);
+        let s: StackCow = s.into();
+        let s: SmallCow<10> = s.into();
+        let s: SmallString<10> = s.into();
+        assert!(s.is_boxed());
+        assert_eq!(s.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_borrowed_conversion() {
+        let input = String::from("hello");
+        let s: StackCow = input.as_str().into();
+        let s: SmallCow<2> = s.into();
+        assert!(s.is_borrowed());
+        let s: StackCow = s.into();
+        assert!(s.is_borrowed());
+    }
+}
@@ -1,18 +1,54 @@
+use alloc::{
+    borrow::Cow,
+    string::{FromUtf8Error, String},
+    vec::Vec,
+};
 use compact_str::CompactString as CompactStr;
 pub use compact_str::ToCompactString;
-use derive_more::{Deref, DerefMut, Display, From, Index, IndexMut, Into};
-use serde::{Deserialize, Serialize};
-use std::{
-    borrow::{Borrow, BorrowMut, Cow},
+use core::{
+    borrow::{Borrow, BorrowMut},
     convert::Infallible,
-    ffi::OsStr,
     fmt::{self, Write as FmtWrite},
     iter::FromIterator,
-    path::Path,
+    ops::{Add, AddAssign},
     str,
     str::{FromStr, Utf8Error},
-    string::FromUtf8Error,
 };
+use derive_more::{Deref, DerefMut, Display, From, Index, IndexMut, Into};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use std::{ffi::OsStr, path::Path};
+
+use crate::{
+    join::{joined_len, push_joined},
+    stack_str::StackStr,
+};
+
+#[cfg(any(feature = "bincode", feature = "borsh"))]
+use crate::binary;
+
+#[cfg(feature = "bincode")]
+use bincode::{
+    de::{BorrowDecoder, Decoder},
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+};
+
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[cfg(feature = "rkyv")]
+use rkyv::{
+    Archive, DeserializeUnsized, Place, SerializeUnsized,
+    rancor::{Fallible, Source},
+    string::{ArchivedString, StringResolver},
+};
+
+#[cfg(any(feature = "arbitrary", feature = "proptest", feature = "quickcheck"))]
+use crate::generators;
+
+#[cfg(feature = "proptest")]
+use proptest::strategy::{BoxedStrategy, Strategy};
 
 #[cfg(feature = "postgres_types")]
 use bytes::BytesMut;
@@ -28,6 +64,26 @@ use axum::response::IntoResponse;
 #[cfg(feature = "axum_types")]
 use axum::body::Body;
 
+#[cfg(feature = "diesel_types")]
+use diesel::{
+    backend::Backend,
+    deserialize::{self, FromSqlRow},
+    expression::AsExpression,
+    serialize,
+    sql_types::Text,
+};
+
+#[cfg(feature = "sqlx_types")]
+use sqlx::{
+    Database, Decode, Encode, MySql, Postgres, Sqlite,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo},
+    sqlite::SqliteArgumentValue,
+};
+
+#[cfg(feature = "rusqlite_types")]
+use rusqlite::types::{FromSqlResult, ToSqlOutput, ValueRef};
+
 #[cfg(feature = "async_graphql")]
 use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
 
@@ -50,6 +106,8 @@ use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value
     PartialOrd,
     Ord,
 )]
+#[cfg_attr(feature = "diesel_types", derive(AsExpression, FromSqlRow))]
+#[cfg_attr(feature = "diesel_types", diesel(sql_type = Text))]
 pub struct StackString(CompactStr);
 
 impl StackString {
@@ -58,11 +116,43 @@ impl StackString {
         Self(CompactStr::new(""))
     }
 
+    /// Construct a `StackString` from a string literal in a const context,
+    /// long literals are stored as a static reference without allocating.
+    #[must_use]
+    pub const fn from_static(s: &'static str) -> Self {
+        Self(CompactStr::const_new(s))
+    }
+
     #[must_use]
     pub fn as_str(&self) -> &str {
         self.0.as_str()
     }
 
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(CompactStr::with_capacity(capacity))
+    }
+
+    /// Concatenate the parts, sizing the result once up front.
+    #[must_use]
+    pub fn concat<T: AsRef<str>>(parts: &[T]) -> Self {
+        Self::join(parts, "")
+    }
+
+    /// Join the parts with `sep`, sizing the result once up front.
+    #[must_use]
+    pub fn join<I, T>(parts: I, sep: &str) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Clone,
+        T: AsRef<str>,
+    {
+        let parts = parts.into_iter();
+        let mut s = Self::with_capacity(joined_len(parts.clone(), sep));
+        push_joined(&mut s.0, parts, sep);
+        s
+    }
+
     #[must_use]
     pub fn split_off(&mut self, index: usize) -> Self {
         Self(self.0.split_off(index))
@@ -99,10 +189,54 @@ impl StackString {
         s
     }
 
+    /// Returns true if the contents are stored inline, a long string from
+    /// [`StackString::from_static`] is neither inline nor heap allocated.
     #[inline]
     #[must_use]
     pub fn is_inline(&self) -> bool {
-        !self.is_heap_allocated()
+        !self.is_heap_allocated() && !self.is_static()
+    }
+
+    /// Returns true if the contents are a static reference kept by
+    /// [`StackString::from_static`].
+    #[inline]
+    #[must_use]
+    pub fn is_static(&self) -> bool {
+        self.0.as_static_str().is_some()
+    }
+}
+
+impl StackStr for StackString {
+    fn as_str(&self) -> &str {
+        self.as_str()
+    }
+
+    fn is_inline(&self) -> bool {
+        self.is_inline()
+    }
+
+    fn from_str_lossless(s: &str) -> Self {
+        s.into()
+    }
+
+    fn from_display(buf: impl fmt::Display) -> Self {
+        Self::from_display(buf)
+    }
+
+    fn from_utf8(v: &[u8]) -> Result<Self, Utf8Error> {
+        Self::from_utf8(v)
+    }
+
+    fn from_utf8_lossy(v: &[u8]) -> Self {
+        Self::from_utf8_lossy(v)
+    }
+
+    fn split_off(&mut self, index: usize) -> Self {
+        self.split_off(index)
+    }
+
+    fn into_string(self) -> String {
+        self.into()
     }
 }
 
@@ -187,12 +321,14 @@ impl AsRef<[u8]> for StackString {
     }
 }
 
+#[cfg(feature = "std")]
 impl AsRef<OsStr> for StackString {
     fn as_ref(&self) -> &OsStr {
         self.as_str().as_ref()
     }
 }
 
+#[cfg(feature = "std")]
 impl AsRef<Path> for StackString {
     fn as_ref(&self) -> &Path {
         Path::new(self)
@@ -214,7 +350,7 @@ impl<'a> PartialEq<Cow<'a, str>> for StackString {
 }
 
 impl<'a> PartialOrd<Cow<'a, str>> for StackString {
-    fn partial_cmp(&self, other: &Cow<'a, str>) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Cow<'a, str>) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], &other[..])
     }
 }
@@ -227,7 +363,7 @@ impl PartialEq<String> for StackString {
 }
 
 impl PartialOrd<String> for StackString {
-    fn partial_cmp(&self, other: &String) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &String) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], &other[..])
     }
 }
@@ -240,7 +376,7 @@ impl PartialEq<str> for StackString {
 }
 
 impl PartialOrd<str> for StackString {
-    fn partial_cmp(&self, other: &str) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &str) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], other)
     }
 }
@@ -253,7 +389,7 @@ impl<'a> PartialEq<&'a str> for StackString {
 }
 
 impl<'a> PartialOrd<&'a str> for StackString {
-    fn partial_cmp(&self, other: &&'a str) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &&'a str) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], &other[..])
     }
 }
@@ -265,7 +401,7 @@ impl PartialEq<StackString> for str {
 }
 
 impl PartialOrd<StackString> for str {
-    fn partial_cmp(&self, other: &StackString) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &StackString) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(self, &other[..])
     }
 }
@@ -277,7 +413,7 @@ impl PartialEq<StackString> for &str {
 }
 
 impl PartialOrd<StackString> for &str {
-    fn partial_cmp(&self, other: &StackString) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &StackString) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(&self[..], &other[..])
     }
 }
@@ -289,6 +425,57 @@ impl FromIterator<char> for StackString {
     }
 }
 
+impl<'a> FromIterator<&'a str> for StackString {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        Self(CompactStr::from_iter(iter))
+    }
+}
+
+impl FromIterator<String> for StackString {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        Self(CompactStr::from_iter(iter))
+    }
+}
+
+impl FromIterator<StackString> for StackString {
+    fn from_iter<I: IntoIterator<Item = StackString>>(iter: I) -> Self {
+        Self(iter.into_iter().map(|s| s.0).collect())
+    }
+}
+
+impl Extend<char> for StackString {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl<'a> Extend<&'a str> for StackString {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl Extend<String> for StackString {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl Add<&str> for StackString {
+    type Output = Self;
+
+    fn add(mut self, rhs: &str) -> Self {
+        self.push_str(rhs);
+        self
+    }
+}
+
+impl AddAssign<&str> for StackString {
+    fn add_assign(&mut self, rhs: &str) {
+        self.push_str(rhs);
+    }
+}
+
 #[cfg(feature = "postgres_types")]
 impl<'a> FromSql<'a> for StackString {
     fn from_sql(
@@ -333,6 +520,117 @@ impl ToSql for StackString {
     }
 }
 
+#[cfg(feature = "diesel_types")]
+impl<DB> serialize::ToSql<Text, DB> for StackString
+where
+    DB: Backend,
+    str: serialize::ToSql<Text, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut serialize::Output<'b, '_, DB>) -> serialize::Result {
+        <str as serialize::ToSql<Text, DB>>::to_sql(self.as_str(), out)
+    }
+}
+
+#[cfg(feature = "diesel_types")]
+impl<DB> deserialize::FromSql<Text, DB> for StackString
+where
+    DB: Backend,
+    *const str: deserialize::FromSql<Text, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        let ptr = <*const str as deserialize::FromSql<Text, DB>>::from_sql(bytes)?;
+        // The pointer is only valid for the lifetime of `bytes`, the contents
+        // are copied out before returning
+        let s = unsafe { &*ptr };
+        Ok(s.into())
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<DB> sqlx::Type<DB> for StackString
+where
+    DB: Database,
+    str: sqlx::Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <str as sqlx::Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <str as sqlx::Type<DB>>::compatible(ty)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<'r, DB> Decode<'r, DB> for StackString
+where
+    DB: Database,
+    &'r str: Decode<'r, DB>,
+{
+    fn decode(value: DB::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        <&'r str as Decode<'r, DB>>::decode(value).map(Into::into)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl Encode<'_, Postgres> for StackString {
+    fn encode_by_ref(
+        &self,
+        buf: &mut PgArgumentBuffer,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <&str as Encode<Postgres>>::encode(self.as_str(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl Encode<'_, MySql> for StackString {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <&str as Encode<MySql>>::encode(self.as_str(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl<'q> Encode<'q, Sqlite> for StackString {
+    fn encode(
+        self,
+        buf: &mut Vec<SqliteArgumentValue<'q>>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <String as Encode<Sqlite>>::encode(self.into(), buf)
+    }
+
+    fn encode_by_ref(
+        &self,
+        buf: &mut Vec<SqliteArgumentValue<'q>>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        <String as Encode<Sqlite>>::encode(self.into(), buf)
+    }
+}
+
+#[cfg(feature = "sqlx_types")]
+impl PgHasArrayType for StackString {
+    fn array_type_info() -> PgTypeInfo {
+        <&str as PgHasArrayType>::array_type_info()
+    }
+
+    fn array_compatible(ty: &PgTypeInfo) -> bool {
+        <&str as PgHasArrayType>::array_compatible(ty)
+    }
+}
+
+#[cfg(feature = "rusqlite_types")]
+impl rusqlite::types::ToSql for StackString {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(ValueRef::Text(self.as_bytes())))
+    }
+}
+
+#[cfg(feature = "rusqlite_types")]
+impl rusqlite::types::FromSql for StackString {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str().map(|s| s.into())
+    }
+}
+
 #[cfg(feature = "utoipa_types")]
 impl PartialSchema for StackString {
     fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
@@ -363,6 +661,27 @@ impl From<StackString> for Body {
     }
 }
 
+/// Concatenate any number of values implementing `AsRef<str>` into a
+/// `StackString`, allocating at most once.
+#[macro_export]
+macro_rules! concat_sstr {
+    ($($s:expr),* $(,)?) => {
+        $crate::StackString::concat::<&str>(&[$(::core::convert::AsRef::<str>::as_ref(&$s)),*])
+    };
+}
+
+/// Build a `StackString`, or a `SmallString` with `sstr!(CAP; "literal")`,
+/// from a string literal at compile time.
+#[macro_export]
+macro_rules! sstr {
+    ($cap:expr; $s:expr) => {
+        $crate::SmallString::<$cap>::from_static($s)
+    };
+    ($s:expr) => {
+        $crate::StackString::from_static($s)
+    };
+}
+
 #[macro_export]
 macro_rules! format_sstr {
     ($($arg:tt)*) => {
@@ -392,14 +711,126 @@ impl ScalarType for StackString {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for StackString {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        generators::arbitrary_string(u, &[crate::MAX_INLINE]).map(Into::into)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for StackString {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        generators::string_strategy(&[crate::MAX_INLINE])
+            .prop_map(Into::into)
+            .boxed()
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+impl quickcheck::Arbitrary for StackString {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        generators::quickcheck_string(g, &[crate::MAX_INLINE]).into()
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let s = String::from(self.as_str());
+        Box::new(quickcheck::Arbitrary::shrink(&s).map(Into::into))
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl Archive for StackString {
+    type Archived = ArchivedString;
+    type Resolver = StringResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedString::resolve_from_str(self.as_str(), resolver, out);
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<S: Fallible + ?Sized> rkyv::Serialize<S> for StackString
+where
+    S::Error: Source,
+    str: SerializeUnsized<S>,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        ArchivedString::serialize_from_str(self.as_str(), serializer)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<D: Fallible + ?Sized> rkyv::Deserialize<StackString, D> for ArchivedString
+where
+    str: DeserializeUnsized<str, D>,
+{
+    fn deserialize(&self, _: &mut D) -> Result<StackString, D::Error> {
+        Ok(self.as_str().into())
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl PartialEq<StackString> for ArchivedString {
+    fn eq(&self, other: &StackString) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl PartialEq<ArchivedString> for StackString {
+    fn eq(&self, other: &ArchivedString) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl bincode::Encode for StackString {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        bincode::Encode::encode(self.as_str(), encoder)
+    }
+}
+
+/// Decodes straight into the inline buffer when the contents fit.
+#[cfg(feature = "bincode")]
+impl<Context> bincode::Decode<Context> for StackString {
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        binary::bincode_decode::<{ crate::MAX_INLINE }, _, _>(decoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<'de, Context> bincode::BorrowDecode<'de, Context> for StackString {
+    fn borrow_decode<D: BorrowDecoder<'de, Context = Context>>(
+        decoder: &mut D,
+    ) -> Result<Self, DecodeError> {
+        bincode::Decode::decode(decoder)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl BorshSerialize for StackString {
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        BorshSerialize::serialize(self.as_str(), writer)
+    }
+}
+
+/// Reads straight into the inline buffer when the contents fit.
+#[cfg(feature = "borsh")]
+impl BorshDeserialize for StackString {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        binary::borsh_deserialize::<{ crate::MAX_INLINE }, _, _>(reader)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rand::{Rng, rng as thread_rng};
 
-    #[cfg(feature = "async_graphql")]
-    use std::future::Future;
-
-    use crate::{MAX_INLINE, StackString};
+    use crate::{MAX_INLINE, StackStr, StackString};
 
     #[test]
     fn test_default() {
@@ -479,15 +910,18 @@ mod tests {
 
     #[test]
     fn test_as_ref() {
-        use std::path::Path;
-
         let s = StackString::from("Hello");
         let st: &str = s.as_ref();
         assert_eq!(st, s.as_str());
         let bt: &[u8] = s.as_ref();
         assert_eq!(bt, s.as_bytes());
-        let pt: &Path = s.as_ref();
-        assert_eq!(pt, Path::new("Hello"));
+        #[cfg(feature = "std")]
+        {
+            use std::path::Path;
+
+            let pt: &Path = s.as_ref();
+            assert_eq!(pt, Path::new("Hello"));
+        }
     }
 
     #[test]
@@ -507,6 +941,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::cmp_owned)]
     fn test_partial_eq_string() {
         assert_eq!(StackString::from("Hello"), String::from("Hello"));
         assert_eq!(StackString::from("Hello"), "Hello");
@@ -519,8 +954,8 @@ mod tests {
     fn test_from_iterator_char() {
         let mut rng = thread_rng();
         let v: Vec<char> = (0..20).map(|_| rng.random::<char>()).collect();
-        let s0: StackString = v.iter().map(|x| *x).collect();
-        let s1: String = v.iter().map(|x| *x).collect();
+        let s0: StackString = v.iter().copied().collect();
+        let s1: String = v.iter().copied().collect();
         assert_eq!(s0, s1);
     }
 
@@ -538,6 +973,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::unnecessary_map_on_constructor)]
     fn test_equality() {
         let s: StackString = "hey".into();
         assert_eq!(Some(&s).map(Into::into), Some("hey"));
@@ -567,12 +1003,232 @@ mod tests {
         assert!(<StackString as ToSql>::accepts(&t));
         let mut buf = BytesMut::new();
         match s.to_sql(&t, &mut buf).unwrap() {
-            IsNull::Yes => assert!(false),
+            IsNull::Yes => panic!(),
             IsNull::No => {}
         }
         assert_eq!(buf.as_ref(), b"Hello There");
     }
 
+    #[cfg(feature = "diesel_types")]
+    #[test]
+    fn test_diesel_sqlite() {
+        use diesel::{
+            Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection, sql_query,
+        };
+
+        diesel::table! {
+            test_strings (id) {
+                id -> Integer,
+                s -> Text,
+                n -> Nullable<Text>,
+            }
+        }
+
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        sql_query("CREATE TABLE test_strings (id INTEGER PRIMARY KEY, s TEXT NOT NULL, n TEXT)")
+            .execute(&mut conn)
+            .unwrap();
+
+        let s = StackString::from("Hello There");
+        diesel::insert_into(test_strings::table)
+            .values((
+                test_strings::id.eq(1),
+                test_strings::s.eq(&s),
+                test_strings::n.eq(Some(&s)),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::insert_into(test_strings::table)
+            .values((
+                test_strings::id.eq(2),
+                test_strings::s.eq(s.clone()),
+                test_strings::n.eq(None::<StackString>),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+
+        let rows: Vec<(StackString, Option<StackString>)> = test_strings::table
+            .select((test_strings::s, test_strings::n))
+            .order(test_strings::id)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(rows, vec![(s.clone(), Some(s.clone())), (s.clone(), None)]);
+
+        let found: StackString = test_strings::table
+            .select(test_strings::s)
+            .filter(test_strings::s.eq(&s))
+            .first(&mut conn)
+            .unwrap();
+        assert_eq!(found, s);
+    }
+
+    #[cfg(feature = "sqlx_types")]
+    #[tokio::test]
+    async fn test_sqlx_sqlite() {
+        use sqlx::{Connection, SqliteConnection};
+
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE test_strings (id INTEGER PRIMARY KEY, s TEXT NOT NULL, n TEXT)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let s = StackString::from("Hello There");
+        sqlx::query("INSERT INTO test_strings (id, s, n) VALUES (1, $1, $2)")
+            .bind(&s)
+            .bind(Some(&s))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO test_strings (id, s, n) VALUES (2, $1, $2)")
+            .bind(s.clone())
+            .bind(None::<StackString>)
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let rows: Vec<(StackString, Option<StackString>)> =
+            sqlx::query_as("SELECT s, n FROM test_strings ORDER BY id")
+                .fetch_all(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(rows, vec![(s.clone(), Some(s.clone())), (s.clone(), None)]);
+    }
+
+    #[cfg(feature = "rusqlite_types")]
+    #[test]
+    fn test_rusqlite() {
+        use rusqlite::{Connection, Error, params};
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE test_strings (id INTEGER PRIMARY KEY, s TEXT NOT NULL, n TEXT)",
+            [],
+        )
+        .unwrap();
+
+        let s = StackString::from("Hello There");
+        conn.execute(
+            "INSERT INTO test_strings (id, s, n) VALUES (1, ?1, ?2)",
+            params![s, Some(&s)],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO test_strings (id, s, n) VALUES (2, ?1, ?2)",
+            params![s, None::<StackString>],
+        )
+        .unwrap();
+
+        let mut stmt = conn
+            .prepare("SELECT s, n FROM test_strings ORDER BY id")
+            .unwrap();
+        let rows: Vec<(StackString, Option<StackString>)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows, vec![(s.clone(), Some(s.clone())), (s.clone(), None)]);
+        let s = &rows[0].0;
+        assert!(s.is_inline());
+
+        let result: Result<StackString, _> =
+            conn.query_row("SELECT CAST(X'FF' AS TEXT)", [], |row| row.get(0));
+        assert!(matches!(result, Err(Error::FromSqlConversionFailure(..))));
+    }
+
+    #[test]
+    fn test_add_extend_collect() {
+        let mut s = StackString::from("hello") + " there";
+        s += "!";
+        assert_eq!(s, "hello there!");
+        s.extend([' ', 'a']);
+        s.extend([" b", " c"]);
+        s.extend([String::from(" d")]);
+        assert_eq!(s, "hello there! a b c d");
+
+        let s: StackString = ["a", "b", "c"].into_iter().collect();
+        assert_eq!(s, "abc");
+        let s: StackString = vec![String::from("a"), String::from("b")]
+            .into_iter()
+            .collect();
+        assert_eq!(s, "ab");
+        let s: StackString = ["x", "y"].into_iter().map(StackString::from).collect();
+        assert_eq!(s, "xy");
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_rkyv() {
+        use rkyv::{rancor::Error, string::ArchivedString};
+
+        let s = StackString::from("hello");
+        let bytes = rkyv::to_bytes::<Error>(&s).unwrap();
+        let archived = rkyv::access::<ArchivedString, Error>(&bytes).unwrap();
+        assert_eq!(archived, "hello");
+        assert_eq!(s, *archived);
+        let t: StackString = rkyv::deserialize::<_, Error>(archived).unwrap();
+        assert_eq!(s, t);
+
+        let mut bytes = rkyv::to_bytes::<Error>(&StackString::from("hello")).unwrap();
+        let index = bytes.iter().position(|b| *b == b'e').unwrap();
+        bytes[index] = 0xc0;
+        assert!(rkyv::access::<ArchivedString, Error>(&bytes).is_err());
+    }
+
+    // archived hash maps need `rkyv/std`, which is enabled by the std feature
+    #[cfg(all(feature = "rkyv", feature = "std"))]
+    #[test]
+    fn test_rkyv_map() {
+        use rkyv::{Archived, rancor::Error};
+        use std::collections::HashMap;
+
+        let map: HashMap<StackString, u32> = [("alpha".into(), 1), ("beta".into(), 2)].into();
+        let bytes = rkyv::to_bytes::<Error>(&map).unwrap();
+        let archived = rkyv::access::<Archived<HashMap<StackString, u32>>, Error>(&bytes).unwrap();
+        assert_eq!(archived.get("beta").map(|v| v.to_native()), Some(2));
+        assert!(archived.get("gamma").is_none());
+    }
+
+    #[cfg(all(feature = "bincode", feature = "borsh"))]
+    #[test]
+    fn test_bincode_borsh() {
+        let config = bincode::config::standard();
+        for input in ["hello", "a string which does not fit inline"] {
+            let s = StackString::from(input);
+            let bytes = bincode::encode_to_vec(&s, config).unwrap();
+            let (t, _): (StackString, _) = bincode::decode_from_slice(&bytes, config).unwrap();
+            assert_eq!(t, input);
+            assert_eq!(t.is_heap_allocated(), input.len() > MAX_INLINE);
+
+            let bytes = borsh::to_vec(&s).unwrap();
+            let t: StackString = borsh::from_slice(&bytes).unwrap();
+            assert_eq!(t, input);
+            assert_eq!(t.is_heap_allocated(), input.len() > MAX_INLINE);
+        }
+    }
+
+    #[test]
+    fn test_from_static() {
+        const SHORT: StackString = StackString::from_static("hello");
+        const LONG: StackString = crate::sstr!("a string literal longer than 24 bytes");
+        static TABLE: [StackString; 2] = [sstr!("alpha"), sstr!("beta")];
+
+        assert_eq!(SHORT, "hello");
+        assert!(SHORT.is_inline());
+        assert_eq!(LONG, "a string literal longer than 24 bytes");
+        assert!(!LONG.is_heap_allocated());
+        assert!(!LONG.is_inline());
+        assert!(LONG.is_static());
+        assert!(!<StackString as StackStr>::is_inline(&LONG));
+        assert!(!SHORT.is_static());
+        assert_eq!(TABLE[1], "beta");
+
+        let mut s = LONG;
+        s.push('!');
+        assert!(s.is_heap_allocated());
+        assert_eq!(LONG, "a string literal longer than 24 bytes");
+    }
+
     #[test]
     fn test_from_display() {
         use std::fmt::Display;
@@ -604,7 +1260,7 @@ mod tests {
         v.extend_from_slice("yes".as_bytes());
         let s = StackString::from_utf8_lossy(&v);
         assert_eq!(s.len(), 20);
-        assert_eq!(s.is_heap_allocated(), false);
+        assert!(!s.is_heap_allocated());
     }
 
     #[test]
@@ -641,7 +1297,6 @@ mod tests {
             Context, EmptyMutation, EmptySubscription, Object, Schema,
             dataloader::{DataLoader, Loader},
         };
-        use async_trait::async_trait;
         use std::{collections::HashMap, convert::Infallible};
 
         struct StackStringLoader;
@@ -652,21 +1307,17 @@ mod tests {
             }
         }
 
-        #[async_trait]
         impl Loader<StackString> for StackStringLoader {
             type Value = StackString;
             type Error = Infallible;
 
-            fn load(
+            async fn load(
                 &self,
                 _: &[StackString],
-            ) -> impl Future<Output = Result<HashMap<StackString, Self::Value>, Self::Error>>
-            {
-                async move {
-                    let mut m = HashMap::new();
-                    m.insert("HELLO".into(), "WORLD".into());
-                    Ok(m)
-                }
+            ) -> Result<HashMap<StackString, Self::Value>, Self::Error> {
+                let mut m = HashMap::new();
+                m.insert("HELLO".into(), "WORLD".into());
+                Ok(m)
             }
         }
 
@@ -0,0 +1,160 @@
+//! Checks that deserializing strings which fit inline doesn't touch the heap.
+//!
+//! This lives in its own test binary since it replaces the global allocator,
+//! allocations are counted per thread so that tests can run concurrently.
+
+use serde::{
+    Deserialize, Deserializer,
+    de::{Visitor, value::Error},
+    forward_to_deserialize_any,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use stack_string::{SmallCow, SmallString, StackString};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Returns the result of `f` along with the number of heap allocations it
+/// made.
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+/// A format which hands out an owned `String` whenever one is asked for, as
+/// some readers which can't borrow from their input do.
+struct OwnedWhenAsked<'a>(&'a str);
+
+impl<'de> Deserializer<'de> for OwnedWhenAsked<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0.to_owned())
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+#[derive(Deserialize)]
+struct Record<'a> {
+    name: SmallString<16>,
+    code: SmallString<4>,
+    #[serde(borrow)]
+    tag: SmallCow<'a, 8>,
+    description: StackString,
+    #[serde(deserialize_with = "stack_string::serde_adapters::trim::deserialize")]
+    trimmed: SmallString<8>,
+    #[serde(deserialize_with = "stack_string::serde_adapters::lowercase::deserialize")]
+    lower: SmallString<8>,
+}
+
+#[test]
+fn test_inline_json_does_not_allocate() {
+    let input = r#"{
+        "name": "sixteen bytes!!!",
+        "code": "abc",
+        "tag": "tag",
+        "description": "twenty four bytes long!!",
+        "trimmed": "  trim me ",
+        "lower": "LOWER"
+    }"#;
+
+    let (record, allocations) = count_allocations(|| serde_json::from_str::<Record>(input));
+    let record = record.unwrap();
+    assert_eq!(allocations, 0);
+
+    assert!(record.name.is_inline());
+    assert_eq!(record.code, "abc");
+    assert!(record.code.is_inline());
+    assert_eq!(record.tag, "tag");
+    assert!(!record.description.is_heap_allocated());
+    assert_eq!(record.trimmed, "trim me");
+    assert_eq!(record.lower, "lower");
+
+    let (value, allocations) =
+        count_allocations(|| serde_json::from_slice::<SmallString<8>>(br#""bytes""#));
+    assert_eq!(value.unwrap(), "bytes");
+    assert_eq!(allocations, 0);
+}
+
+#[test]
+fn test_inline_str_is_not_requested_as_string() {
+    let (value, allocations) =
+        count_allocations(|| SmallString::<8>::deserialize(OwnedWhenAsked("short")));
+    assert_eq!(value.unwrap(), "short");
+    assert_eq!(allocations, 0);
+}
+
+#[test]
+fn test_boxed_json_allocates_once() {
+    let input = format!(r#""{}""#, "x".repeat(100));
+
+    let (value, allocations) = count_allocations(|| serde_json::from_str::<SmallString<8>>(&input));
+    assert!(!value.unwrap().is_inline());
+    assert_eq!(allocations, 1);
+}
+
+#[cfg(feature = "postcard")]
+#[test]
+fn test_inline_postcard_does_not_allocate() {
+    use serde::Serialize;
+    use stack_string::StackCow;
+
+    #[derive(Serialize, Deserialize)]
+    struct Key<'a> {
+        name: SmallString<16>,
+        description: StackString,
+        #[serde(borrow)]
+        tag: StackCow<'a>,
+    }
+
+    let key = Key {
+        name: "sixteen bytes!!!".into(),
+        description: "twenty four bytes long!!".into(),
+        tag: "tag".into(),
+    };
+    let mut buf = [0u8; 64];
+    let bytes = postcard::to_slice(&key, &mut buf).unwrap();
+
+    let (decoded, allocations) = count_allocations(|| postcard::from_bytes::<Key>(bytes));
+    let decoded = decoded.unwrap();
+    assert_eq!(allocations, 0);
+    assert!(decoded.name.is_inline());
+    assert!(!decoded.description.is_heap_allocated());
+    assert_eq!(decoded.tag, "tag");
+}